@@ -0,0 +1,75 @@
+/*
+ * C interface to twenty-first: arithmetic over the prime field with
+ * p = 2^64 - 2^32 + 1, Tip5 hashing, and Merkle tree inclusion proof
+ * verification.
+ *
+ * Conventions:
+ * - Every function returns a tf_status and writes its result through an
+ *   out-pointer. The out-pointer is written to only if the status is TF_OK.
+ * - Field elements are passed as uint64_t in canonical representation, i.e.,
+ *   strictly smaller than p. Non-canonical inputs are rejected.
+ * - A digest is passed as a pointer to TF_DIGEST_LEN consecutive field
+ *   elements. Lists of digests are flat arrays of field elements, together
+ *   with the number of digests.
+ * - Input arrays of length 0 may be NULL.
+ */
+
+#ifndef TWENTY_FIRST_H
+#define TWENTY_FIRST_H
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+#define TF_ABI_VERSION 1
+#define TF_DIGEST_LEN 5
+
+typedef enum tf_status {
+    TF_OK = 0,
+    TF_NULL_POINTER = 1,
+    TF_NON_CANONICAL = 2,
+    TF_DIVISION_BY_ZERO = 3,
+    TF_LENGTH_OVERFLOW = 4,
+} tf_status;
+
+/* The ABI only changes in backwards-compatible ways while this stays the same. */
+uint32_t tf_abi_version(void);
+
+tf_status tf_bfe_add(uint64_t lhs, uint64_t rhs, uint64_t *out);
+tf_status tf_bfe_sub(uint64_t lhs, uint64_t rhs, uint64_t *out);
+tf_status tf_bfe_mul(uint64_t lhs, uint64_t rhs, uint64_t *out);
+
+/* Returns TF_DIVISION_BY_ZERO for input 0. */
+tf_status tf_bfe_inverse(uint64_t element, uint64_t *out);
+tf_status tf_bfe_pow(uint64_t base, uint64_t exponent, uint64_t *out);
+
+/* Hash input_len field elements. Writes one digest to out. */
+tf_status tf_tip5_hash_varlen(const uint64_t *input, size_t input_len, uint64_t *out);
+
+/* Hash two digests, for example the two children of a Merkle tree node. */
+tf_status tf_tip5_hash_pair(const uint64_t *left, const uint64_t *right, uint64_t *out);
+
+/*
+ * Verify that num_leafs leafs are in the Merkle tree of the given height and
+ * root. The i-th leaf has index leaf_indices[i]. Writes true to out if and
+ * only if the proof is valid; a malformed proof is invalid.
+ */
+tf_status tf_merkle_verify(
+    const uint64_t *root,
+    size_t tree_height,
+    const size_t *leaf_indices,
+    const uint64_t *leafs,
+    size_t num_leafs,
+    const uint64_t *authentication_structure,
+    size_t authentication_structure_len,
+    bool *out);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* TWENTY_FIRST_H */
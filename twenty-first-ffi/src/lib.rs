@@ -0,0 +1,380 @@
+//! A C ABI for [twenty-first](twenty_first), exposing arithmetic over the
+//! [`BFieldElement`]s, [`Tip5`] hashing, and Merkle tree inclusion proof verification.
+//!
+//! The corresponding C header is `include/twenty_first.h`.
+//!
+//! Conventions shared by all exported functions:
+//!
+//! - Every function returns a [`Status`] and writes its result through an out-pointer. The
+//!   out-pointer is written to only if the status is [`Status::Ok`].
+//! - Field elements are passed as `uint64_t` in canonical representation, _i.e._, strictly
+//!   smaller than [`BFieldElement::P`]. Non-canonical inputs are rejected.
+//! - A [`Digest`] is passed as a pointer to [`Digest::LEN`] consecutive field elements. Lists of
+//!   digests are flat arrays of field elements, together with the number of digests.
+//! - Input arrays of length 0 may be null.
+//!
+//! The ABI only ever changes in a backwards-compatible way while [`ABI_VERSION`] stays the
+//! same.
+
+use std::marker::PhantomData;
+use std::slice;
+
+use twenty_first::prelude::AlgebraicHasher;
+use twenty_first::prelude::BFieldElement;
+use twenty_first::prelude::Digest;
+use twenty_first::prelude::Inverse;
+use twenty_first::prelude::MerkleTreeInclusionProof;
+use twenty_first::prelude::Tip5;
+
+/// The version of the C ABI. See also [`tf_abi_version`].
+pub const ABI_VERSION: u32 = 1;
+
+/// The outcome of a call to any of the exported functions.
+#[repr(C)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Status {
+    Ok = 0,
+    NullPointer = 1,
+    NonCanonical = 2,
+    DivisionByZero = 3,
+    LengthOverflow = 4,
+}
+
+type Result<T> = std::result::Result<T, Status>;
+
+#[no_mangle]
+pub extern "C" fn tf_abi_version() -> u32 {
+    ABI_VERSION
+}
+
+/// # Safety
+///
+/// `out` must be null or valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn tf_bfe_add(lhs: u64, rhs: u64, out: *mut u64) -> Status {
+    let result = || Ok((bfe(lhs)? + bfe(rhs)?).value());
+    write(out, result())
+}
+
+/// # Safety
+///
+/// `out` must be null or valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn tf_bfe_sub(lhs: u64, rhs: u64, out: *mut u64) -> Status {
+    let result = || Ok((bfe(lhs)? - bfe(rhs)?).value());
+    write(out, result())
+}
+
+/// # Safety
+///
+/// `out` must be null or valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn tf_bfe_mul(lhs: u64, rhs: u64, out: *mut u64) -> Status {
+    let result = || Ok((bfe(lhs)? * bfe(rhs)?).value());
+    write(out, result())
+}
+
+/// The multiplicative inverse. Returns [`Status::DivisionByZero`] for input 0.
+///
+/// # Safety
+///
+/// `out` must be null or valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn tf_bfe_inverse(element: u64, out: *mut u64) -> Status {
+    let result = || match bfe(element)? {
+        zero if zero.value() == 0 => Err(Status::DivisionByZero),
+        element => Ok(element.inverse().value()),
+    };
+    write(out, result())
+}
+
+/// # Safety
+///
+/// `out` must be null or valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn tf_bfe_pow(base: u64, exponent: u64, out: *mut u64) -> Status {
+    let result = || Ok(bfe(base)?.mod_pow(exponent).value());
+    write(out, result())
+}
+
+/// Hash `input_len` field elements. Writes one digest to `out`.
+///
+/// # Safety
+///
+/// - `input` must be valid for reads of `input_len` elements, or `input_len` must be 0.
+/// - `out` must be null or valid for writes of one digest.
+#[no_mangle]
+pub unsafe extern "C" fn tf_tip5_hash_varlen(
+    input: *const u64,
+    input_len: usize,
+    out: *mut u64,
+) -> Status {
+    let result = || {
+        let input = bfes(read(input, input_len)?)?;
+        Ok(Tip5::hash_varlen(&input))
+    };
+    write_digest(out, result())
+}
+
+/// Hash two digests, for example the two children of a Merkle tree node. Writes one digest to
+/// `out`.
+///
+/// # Safety
+///
+/// - `left` and `right` must be valid for reads of one digest each.
+/// - `out` must be null or valid for writes of one digest.
+#[no_mangle]
+pub unsafe extern "C" fn tf_tip5_hash_pair(
+    left: *const u64,
+    right: *const u64,
+    out: *mut u64,
+) -> Status {
+    let result = || {
+        let left = read_digest(left)?;
+        let right = read_digest(right)?;
+        Ok(Tip5::hash_pair(left, right))
+    };
+    write_digest(out, result())
+}
+
+/// Verify that `num_leafs` leafs are in the Merkle tree of the given height and root. The
+/// `i`th leaf has index `leaf_indices[i]`. The authentication structure consists of
+/// `authentication_structure_len` digests, as produced by `MerkleTree::authentication_structure`.
+///
+/// Writes `true` to `out` if and only if the proof is valid. A malformed proof is invalid; it
+/// does not result in an error status.
+///
+/// # Safety
+///
+/// - `root` must be valid for reads of one digest.
+/// - `leaf_indices` must be valid for reads of `num_leafs` elements, and `leafs` for reads of
+///   `num_leafs` digests, or `num_leafs` must be 0.
+/// - `authentication_structure` must be valid for reads of `authentication_structure_len`
+///   digests, or `authentication_structure_len` must be 0.
+/// - `out` must be null or valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn tf_merkle_verify(
+    root: *const u64,
+    tree_height: usize,
+    leaf_indices: *const usize,
+    leafs: *const u64,
+    num_leafs: usize,
+    authentication_structure: *const u64,
+    authentication_structure_len: usize,
+    out: *mut bool,
+) -> Status {
+    let result = || {
+        let root = read_digest(root)?;
+        let leaf_indices = read(leaf_indices, num_leafs)?;
+        let leafs = read_digests(leafs, num_leafs)?;
+        let authentication_structure =
+            read_digests(authentication_structure, authentication_structure_len)?;
+
+        let inclusion_proof = MerkleTreeInclusionProof::<Tip5> {
+            tree_height,
+            indexed_leafs: leaf_indices.iter().copied().zip(leafs).collect(),
+            authentication_structure,
+            _hasher: PhantomData,
+        };
+        Ok(inclusion_proof.verify(root))
+    };
+    write(out, result())
+}
+
+fn bfe(value: u64) -> Result<BFieldElement> {
+    let bfe = BFieldElement::is_canonical(value).then(|| BFieldElement::new(value));
+    bfe.ok_or(Status::NonCanonical)
+}
+
+fn bfes(values: &[u64]) -> Result<Vec<BFieldElement>> {
+    values.iter().map(|&value| bfe(value)).collect()
+}
+
+/// # Safety
+///
+/// `ptr` must be valid for reads of `len` elements, or `len` must be 0.
+unsafe fn read<'a, T>(ptr: *const T, len: usize) -> Result<&'a [T]> {
+    match (len, ptr.is_null()) {
+        (0, _) => Ok(&[]),
+        (_, true) => Err(Status::NullPointer),
+        (_, false) => Ok(slice::from_raw_parts(ptr, len)),
+    }
+}
+
+/// # Safety
+///
+/// `ptr` must be valid for reads of one digest.
+unsafe fn read_digest(ptr: *const u64) -> Result<Digest> {
+    let digests = read_digests(ptr, 1)?;
+    Ok(digests[0])
+}
+
+/// # Safety
+///
+/// `ptr` must be valid for reads of `num_digests` digests, or `num_digests` must be 0.
+unsafe fn read_digests(ptr: *const u64, num_digests: usize) -> Result<Vec<Digest>> {
+    let len = num_digests
+        .checked_mul(Digest::LEN)
+        .ok_or(Status::LengthOverflow)?;
+    let elements = bfes(read(ptr, len)?)?;
+    let digests = elements
+        .chunks_exact(Digest::LEN)
+        .map(|chunk| Digest::new(chunk.try_into().unwrap()))
+        .collect();
+    Ok(digests)
+}
+
+/// # Safety
+///
+/// `out` must be null or valid for writes.
+unsafe fn write<T>(out: *mut T, result: Result<T>) -> Status {
+    if out.is_null() {
+        return Status::NullPointer;
+    }
+    match result {
+        Ok(value) => {
+            out.write(value);
+            Status::Ok
+        }
+        Err(status) => status,
+    }
+}
+
+/// # Safety
+///
+/// `out` must be null or valid for writes of one digest.
+unsafe fn write_digest(out: *mut u64, result: Result<Digest>) -> Status {
+    if out.is_null() {
+        return Status::NullPointer;
+    }
+    match result {
+        Ok(digest) => {
+            let values = digest.values().map(|element| element.value());
+            slice::from_raw_parts_mut(out, Digest::LEN).copy_from_slice(&values);
+            Status::Ok
+        }
+        Err(status) => status,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ptr;
+
+    use twenty_first::prelude::CpuParallel;
+    use twenty_first::prelude::MerkleTree;
+
+    use super::*;
+
+    fn flatten(digests: &[Digest]) -> Vec<u64> {
+        digests
+            .iter()
+            .flat_map(|digest| digest.values())
+            .map(|element| element.value())
+            .collect()
+    }
+
+    #[test]
+    fn field_arithmetic_agrees_with_rust_implementation() {
+        let (lhs, rhs) = (
+            BFieldElement::new(1 << 40),
+            BFieldElement::new(BFieldElement::MAX),
+        );
+        let mut out = 0;
+
+        unsafe {
+            assert_eq!(Status::Ok, tf_bfe_add(lhs.value(), rhs.value(), &mut out));
+            assert_eq!((lhs + rhs).value(), out);
+            assert_eq!(Status::Ok, tf_bfe_sub(lhs.value(), rhs.value(), &mut out));
+            assert_eq!((lhs - rhs).value(), out);
+            assert_eq!(Status::Ok, tf_bfe_mul(lhs.value(), rhs.value(), &mut out));
+            assert_eq!((lhs * rhs).value(), out);
+            assert_eq!(Status::Ok, tf_bfe_inverse(lhs.value(), &mut out));
+            assert_eq!(lhs.inverse().value(), out);
+            assert_eq!(Status::Ok, tf_bfe_pow(lhs.value(), 3, &mut out));
+            assert_eq!(lhs.mod_pow(3).value(), out);
+        }
+    }
+
+    #[test]
+    fn invalid_field_arithmetic_is_reported() {
+        let mut out = 42;
+        unsafe {
+            let status = tf_bfe_add(BFieldElement::P, 1, &mut out);
+            assert_eq!(Status::NonCanonical, status);
+            assert_eq!(Status::DivisionByZero, tf_bfe_inverse(0, &mut out));
+            assert_eq!(Status::NullPointer, tf_bfe_mul(1, 2, ptr::null_mut()));
+        }
+        assert_eq!(42, out);
+    }
+
+    #[test]
+    fn hashing_agrees_with_rust_implementation() {
+        let input = [1, 2, 3];
+        let mut out = [0; Digest::LEN];
+        let status = unsafe { tf_tip5_hash_varlen(input.as_ptr(), input.len(), out.as_mut_ptr()) };
+        assert_eq!(Status::Ok, status);
+        let expected = Tip5::hash_varlen(&bfes(&input).unwrap());
+        assert_eq!(flatten(&[expected]), out);
+
+        let status = unsafe { tf_tip5_hash_varlen(ptr::null(), 0, out.as_mut_ptr()) };
+        assert_eq!(Status::Ok, status);
+        assert_eq!(flatten(&[Tip5::hash_varlen(&[])]), out);
+
+        let (left, right) = (expected, Digest::default());
+        let (left_ptr, right_ptr) = (flatten(&[left]), flatten(&[right]));
+        let status =
+            unsafe { tf_tip5_hash_pair(left_ptr.as_ptr(), right_ptr.as_ptr(), out.as_mut_ptr()) };
+        assert_eq!(Status::Ok, status);
+        assert_eq!(flatten(&[Tip5::hash_pair(left, right)]), out);
+    }
+
+    #[test]
+    fn hashing_rejects_invalid_input() {
+        let mut out = [0; Digest::LEN];
+        unsafe {
+            let status = tf_tip5_hash_varlen(ptr::null(), 1, out.as_mut_ptr());
+            assert_eq!(Status::NullPointer, status);
+            let status = tf_tip5_hash_varlen([u64::MAX].as_ptr(), 1, out.as_mut_ptr());
+            assert_eq!(Status::NonCanonical, status);
+        }
+    }
+
+    #[test]
+    fn merkle_inclusion_proof_verifies() {
+        let leafs = (0..8)
+            .map(|i| Tip5::hash_varlen(&[BFieldElement::new(i)]))
+            .collect::<Vec<_>>();
+        let tree = MerkleTree::<Tip5>::new::<CpuParallel>(&leafs).unwrap();
+        let root = flatten(&[tree.root()]);
+        let leaf_indices = [1_usize, 6];
+        let authentication_structure = tree.authentication_structure(&leaf_indices).unwrap();
+        let authentication_structure_len = authentication_structure.len();
+        let authentication_structure = flatten(&authentication_structure);
+
+        let verify = |opened_leafs: &[Digest], out: &mut bool| unsafe {
+            tf_merkle_verify(
+                root.as_ptr(),
+                3,
+                leaf_indices.as_ptr(),
+                flatten(opened_leafs).as_ptr(),
+                opened_leafs.len(),
+                authentication_structure.as_ptr(),
+                authentication_structure_len,
+                out,
+            )
+        };
+
+        let mut is_valid = false;
+        assert_eq!(Status::Ok, verify(&[leafs[1], leafs[6]], &mut is_valid));
+        assert!(is_valid);
+
+        assert_eq!(Status::Ok, verify(&[leafs[1], leafs[5]], &mut is_valid));
+        assert!(!is_valid);
+    }
+
+    #[test]
+    fn abi_version_is_exported() {
+        assert_eq!(ABI_VERSION, tf_abi_version());
+    }
+}
@@ -0,0 +1,582 @@
+//! Python bindings for [twenty-first](twenty_first), published as the module
+//! `twenty_first_py`.
+//!
+//! The module provides
+//! - the field elements [`BFieldElement`] and [`XFieldElement`],
+//! - univariate [`Polynomial`]s over the [`BFieldElement`]s,
+//! - the [`Tip5`] hash function and sponge, and
+//! - [`MerkleTree`]s over [`Digest`]s, including inclusion proof verification.
+//!
+//! Field elements are constructed from Python `int`s in canonical representation. Non-canonical
+//! values raise a `ValueError`.
+
+// pyo3's macros convert every returned error, which clippy mistakes for a useless conversion
+#![allow(clippy::useless_conversion)]
+
+use std::hash::Hash;
+use std::hash::Hasher;
+use std::marker::PhantomData;
+
+use pyo3::exceptions::PyValueError;
+use pyo3::exceptions::PyZeroDivisionError;
+use pyo3::prelude::*;
+use twenty_first::prelude::AlgebraicHasher;
+use twenty_first::prelude::BFieldElement;
+use twenty_first::prelude::CpuParallel;
+use twenty_first::prelude::Digest;
+use twenty_first::prelude::Inverse;
+use twenty_first::prelude::MerkleTree;
+use twenty_first::prelude::MerkleTreeInclusionProof;
+use twenty_first::prelude::ModPowU32;
+use twenty_first::prelude::Polynomial;
+use twenty_first::prelude::Sponge;
+use twenty_first::prelude::Tip5;
+use twenty_first::prelude::XFieldElement;
+use twenty_first::util_types::algebraic_hasher::RATE;
+
+#[pymodule]
+fn twenty_first_py(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_class::<PyBFieldElement>()?;
+    module.add_class::<PyXFieldElement>()?;
+    module.add_class::<PyPolynomial>()?;
+    module.add_class::<PyDigest>()?;
+    module.add_class::<PyTip5>()?;
+    module.add_class::<PyMerkleTree>()?;
+    Ok(())
+}
+
+#[pyclass(name = "BFieldElement", module = "twenty_first_py", eq, hash, frozen)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct PyBFieldElement(BFieldElement);
+
+#[pymethods]
+impl PyBFieldElement {
+    #[new]
+    fn new(value: u64) -> PyResult<Self> {
+        Ok(Self(bfe(value)?))
+    }
+
+    #[staticmethod]
+    fn zero() -> Self {
+        Self(BFieldElement::new(0))
+    }
+
+    #[staticmethod]
+    fn one() -> Self {
+        Self(BFieldElement::new(1))
+    }
+
+    #[getter]
+    fn value(&self) -> u64 {
+        self.0.value()
+    }
+
+    fn inverse(&self) -> PyResult<Self> {
+        if self.0.value() == 0 {
+            return Err(PyZeroDivisionError::new_err("zero has no inverse"));
+        }
+        Ok(Self(self.0.inverse()))
+    }
+
+    fn __add__(&self, other: &Self) -> Self {
+        Self(self.0 + other.0)
+    }
+
+    fn __sub__(&self, other: &Self) -> Self {
+        Self(self.0 - other.0)
+    }
+
+    fn __mul__(&self, other: &Self) -> Self {
+        Self(self.0 * other.0)
+    }
+
+    fn __truediv__(&self, other: &Self) -> PyResult<Self> {
+        Ok(Self(self.0 * other.inverse()?.0))
+    }
+
+    fn __neg__(&self) -> Self {
+        Self(-self.0)
+    }
+
+    fn __pow__(&self, exponent: u64, modulo: Option<u64>) -> PyResult<Self> {
+        if modulo.is_some() {
+            return Err(PyValueError::new_err("modulus is not supported"));
+        }
+        Ok(Self(self.0.mod_pow(exponent)))
+    }
+
+    fn __int__(&self) -> u64 {
+        self.0.value()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("BFieldElement({})", self.0.value())
+    }
+}
+
+#[pyclass(name = "XFieldElement", module = "twenty_first_py", eq, hash, frozen)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct PyXFieldElement(XFieldElement);
+
+#[pymethods]
+impl PyXFieldElement {
+    /// Construct an extension field element from its coefficients, the constant term first.
+    #[new]
+    fn new(coefficients: [PyBFieldElement; 3]) -> Self {
+        Self(XFieldElement::new(coefficients.map(|c| c.0)))
+    }
+
+    #[getter]
+    fn coefficients(&self) -> Vec<PyBFieldElement> {
+        self.0.coefficients.map(PyBFieldElement).to_vec()
+    }
+
+    fn inverse(&self) -> PyResult<Self> {
+        if self.0 == XFieldElement::new([BFieldElement::new(0); 3]) {
+            return Err(PyZeroDivisionError::new_err("zero has no inverse"));
+        }
+        Ok(Self(self.0.inverse()))
+    }
+
+    fn __add__(&self, other: &Self) -> Self {
+        Self(self.0 + other.0)
+    }
+
+    fn __sub__(&self, other: &Self) -> Self {
+        Self(self.0 - other.0)
+    }
+
+    fn __mul__(&self, other: &Self) -> Self {
+        Self(self.0 * other.0)
+    }
+
+    fn __truediv__(&self, other: &Self) -> PyResult<Self> {
+        Ok(Self(self.0 * other.inverse()?.0))
+    }
+
+    fn __neg__(&self) -> Self {
+        Self(-self.0)
+    }
+
+    fn __pow__(&self, exponent: u32, modulo: Option<u64>) -> PyResult<Self> {
+        if modulo.is_some() {
+            return Err(PyValueError::new_err("modulus is not supported"));
+        }
+        Ok(Self(self.0.mod_pow_u32(exponent)))
+    }
+
+    fn __repr__(&self) -> String {
+        let [c0, c1, c2] = self.0.coefficients.map(|c| c.value());
+        format!("XFieldElement([{c0}, {c1}, {c2}])")
+    }
+}
+
+/// A univariate polynomial over the [`BFieldElement`]s.
+#[pyclass(name = "Polynomial", module = "twenty_first_py", eq, hash, frozen)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PyPolynomial(Polynomial<BFieldElement>);
+
+impl PyPolynomial {
+    /// The coefficients without leading zeros.
+    fn trimmed_coefficients(&self) -> &[BFieldElement] {
+        let num_coefficients = usize::try_from(self.0.degree() + 1).unwrap_or(0);
+        &self.0.coefficients[..num_coefficients]
+    }
+}
+
+// Not derived: equality ignores leading zeros, so hashing must as well.
+impl Hash for PyPolynomial {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.trimmed_coefficients().hash(state);
+    }
+}
+
+#[pymethods]
+impl PyPolynomial {
+    /// Construct a polynomial from its coefficients, the constant term first.
+    #[new]
+    fn new(coefficients: Vec<PyBFieldElement>) -> Self {
+        Self(Polynomial::new(bfes(coefficients)))
+    }
+
+    /// The polynomial of least degree that evaluates to `values` on `domain`.
+    #[staticmethod]
+    fn interpolate(domain: Vec<PyBFieldElement>, values: Vec<PyBFieldElement>) -> PyResult<Self> {
+        if domain.is_empty() || domain.len() != values.len() {
+            let msg = "domain and values must be non-empty and of equal length";
+            return Err(PyValueError::new_err(msg));
+        }
+        let domain = bfes(domain);
+        if (1..domain.len()).any(|i| domain[..i].contains(&domain[i])) {
+            return Err(PyValueError::new_err("domain must not contain duplicates"));
+        }
+        Ok(Self(Polynomial::interpolate(&domain, &bfes(values))))
+    }
+
+    /// The monic polynomial of least degree with the given roots.
+    #[staticmethod]
+    fn zerofier(roots: Vec<PyBFieldElement>) -> Self {
+        Self(Polynomial::zerofier(&bfes(roots)))
+    }
+
+    #[getter]
+    fn coefficients(&self) -> Vec<PyBFieldElement> {
+        self.trimmed_coefficients()
+            .iter()
+            .copied()
+            .map(PyBFieldElement)
+            .collect()
+    }
+
+    /// The degree of the polynomial. The zero polynomial has degree -1.
+    #[getter]
+    fn degree(&self) -> isize {
+        self.0.degree()
+    }
+
+    fn evaluate(&self, x: PyBFieldElement) -> PyBFieldElement {
+        PyBFieldElement(self.0.evaluate(x.0))
+    }
+
+    fn formal_derivative(&self) -> Self {
+        Self(self.0.formal_derivative())
+    }
+
+    /// Divide by `divisor`, returning quotient and remainder.
+    fn divide(&self, divisor: &Self) -> PyResult<(Self, Self)> {
+        if divisor.0.degree() < 0 {
+            return Err(PyZeroDivisionError::new_err("division by zero polynomial"));
+        }
+        let (quotient, remainder) = self.0.divide(&divisor.0);
+        Ok((Self(quotient), Self(remainder)))
+    }
+
+    fn __add__(&self, other: &Self) -> Self {
+        Self(self.0.clone() + other.0.clone())
+    }
+
+    fn __sub__(&self, other: &Self) -> Self {
+        Self(self.0.clone() - other.0.clone())
+    }
+
+    fn __mul__(&self, other: &Self) -> Self {
+        Self(self.0.clone() * other.0.clone())
+    }
+
+    fn __floordiv__(&self, other: &Self) -> PyResult<Self> {
+        Ok(self.divide(other)?.0)
+    }
+
+    fn __mod__(&self, other: &Self) -> PyResult<Self> {
+        Ok(self.divide(other)?.1)
+    }
+
+    fn __neg__(&self) -> Self {
+        Self(-self.0.clone())
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Polynomial({})", self.0)
+    }
+}
+
+#[pyclass(name = "Digest", module = "twenty_first_py", eq, hash, frozen)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct PyDigest(Digest);
+
+#[pymethods]
+impl PyDigest {
+    #[new]
+    fn new(elements: [PyBFieldElement; Digest::LEN]) -> Self {
+        Self(Digest::new(elements.map(|e| e.0)))
+    }
+
+    #[staticmethod]
+    fn from_hex(hex: &str) -> PyResult<Self> {
+        let digest = Digest::try_from_hex(hex).map_err(value_error)?;
+        Ok(Self(digest))
+    }
+
+    #[pyo3(name = "to_hex")]
+    fn hex(&self) -> String {
+        self.0.to_hex()
+    }
+
+    #[getter]
+    fn elements(&self) -> Vec<PyBFieldElement> {
+        self.0.values().map(PyBFieldElement).to_vec()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Digest.from_hex(\"{}\")", self.0.to_hex())
+    }
+}
+
+/// The Tip5 hash function. An instance is a sponge in variable-length mode; hashing functions
+/// are static methods.
+#[pyclass(name = "Tip5", module = "twenty_first_py")]
+#[derive(Debug, Clone)]
+pub struct PyTip5(Tip5);
+
+#[pymethods]
+impl PyTip5 {
+    #[new]
+    fn new() -> Self {
+        Self(Tip5::init())
+    }
+
+    #[staticmethod]
+    fn hash_varlen(input: Vec<PyBFieldElement>) -> PyDigest {
+        PyDigest(Tip5::hash_varlen(&bfes(input)))
+    }
+
+    #[staticmethod]
+    fn hash_pair(left: PyDigest, right: PyDigest) -> PyDigest {
+        PyDigest(Tip5::hash_pair(left.0, right.0))
+    }
+
+    /// Absorb exactly `RATE` = 10 elements.
+    fn absorb(&mut self, input: [PyBFieldElement; RATE]) {
+        self.0.absorb(input.map(|e| e.0));
+    }
+
+    /// Pad the input and absorb it, in chunks of `RATE` = 10 elements.
+    fn pad_and_absorb_all(&mut self, input: Vec<PyBFieldElement>) {
+        self.0.pad_and_absorb_all(&bfes(input));
+    }
+
+    /// Squeeze `RATE` = 10 elements.
+    fn squeeze(&mut self) -> Vec<PyBFieldElement> {
+        self.0.squeeze().map(PyBFieldElement).to_vec()
+    }
+
+    fn sample_scalars(&mut self, num_scalars: usize) -> Vec<PyXFieldElement> {
+        let scalars = self.0.sample_scalars(num_scalars);
+        scalars.into_iter().map(PyXFieldElement).collect()
+    }
+
+    /// Sample `num_indices` indices in `[0, upper_bound)`. The upper bound must be a power of 2.
+    fn sample_indices(&mut self, upper_bound: u32, num_indices: usize) -> PyResult<Vec<u32>> {
+        if !upper_bound.is_power_of_two() {
+            return Err(PyValueError::new_err("upper bound must be a power of 2"));
+        }
+        Ok(self.0.sample_indices(upper_bound, num_indices))
+    }
+}
+
+#[pyclass(name = "MerkleTree", module = "twenty_first_py", frozen)]
+#[derive(Debug, Clone)]
+pub struct PyMerkleTree(MerkleTree<Tip5>);
+
+#[pymethods]
+impl PyMerkleTree {
+    /// Build a Merkle tree. The number of leafs must be a power of 2.
+    #[new]
+    fn new(leafs: Vec<PyDigest>) -> PyResult<Self> {
+        let leafs = leafs.into_iter().map(|leaf| leaf.0).collect::<Vec<_>>();
+        let tree = MerkleTree::new::<CpuParallel>(&leafs).map_err(value_error)?;
+        Ok(Self(tree))
+    }
+
+    #[getter]
+    fn root(&self) -> PyDigest {
+        PyDigest(self.0.root())
+    }
+
+    #[getter]
+    fn num_leafs(&self) -> usize {
+        self.0.num_leafs()
+    }
+
+    #[getter]
+    fn height(&self) -> usize {
+        self.0.height()
+    }
+
+    fn leaf(&self, index: usize) -> Option<PyDigest> {
+        self.0.leaf(index).map(PyDigest)
+    }
+
+    /// The digests needed to authenticate the leafs with the given indices, de-duplicated.
+    fn authentication_structure(&self, leaf_indices: Vec<usize>) -> PyResult<Vec<PyDigest>> {
+        let structure = self
+            .0
+            .authentication_structure(&leaf_indices)
+            .map_err(value_error)?;
+        Ok(structure.into_iter().map(PyDigest).collect())
+    }
+
+    /// Verify that the leafs with the given indices are in the Merkle tree of the given height
+    /// and root.
+    #[staticmethod]
+    fn verify(
+        root: PyDigest,
+        tree_height: usize,
+        leaf_indices: Vec<usize>,
+        leafs: Vec<PyDigest>,
+        authentication_structure: Vec<PyDigest>,
+    ) -> bool {
+        if leaf_indices.len() != leafs.len() {
+            return false;
+        }
+        let inclusion_proof = MerkleTreeInclusionProof::<Tip5> {
+            tree_height,
+            indexed_leafs: leaf_indices
+                .into_iter()
+                .zip(leafs.into_iter().map(|leaf| leaf.0))
+                .collect(),
+            authentication_structure: authentication_structure.into_iter().map(|d| d.0).collect(),
+            _hasher: PhantomData,
+        };
+        inclusion_proof.verify(root.0)
+    }
+}
+
+fn bfe(value: u64) -> PyResult<BFieldElement> {
+    let bfe = BFieldElement::is_canonical(value).then(|| BFieldElement::new(value));
+    bfe.ok_or_else(|| PyValueError::new_err(format!("non-canonical field element: {value}")))
+}
+
+fn bfes(elements: Vec<PyBFieldElement>) -> Vec<BFieldElement> {
+    elements.into_iter().map(|e| e.0).collect()
+}
+
+fn value_error(err: impl std::error::Error) -> PyErr {
+    PyValueError::new_err(err.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Once;
+
+    use pyo3::types::PyDict;
+
+    use super::*;
+
+    /// Run the given Python code with the module `twenty_first_py` imported as `tf`.
+    fn run_python(code: &str) {
+        static INIT: Once = Once::new();
+        INIT.call_once(|| {
+            pyo3::append_to_inittab!(twenty_first_py);
+            pyo3::prepare_freethreaded_python();
+        });
+
+        Python::with_gil(|py| {
+            let globals = PyDict::new_bound(py);
+            let module = py.import_bound("twenty_first_py").unwrap();
+            globals.set_item("tf", module).unwrap();
+            if let Err(err) = py.run_bound(code, Some(&globals), None) {
+                err.display(py);
+                panic!("Python code failed: {err}");
+            }
+        });
+    }
+
+    #[test]
+    fn b_field_element() {
+        run_python(
+            r#"
+a, b = tf.BFieldElement(3), tf.BFieldElement(5)
+assert a * b == tf.BFieldElement(15)
+assert (a / b) * b == a
+assert a - a == tf.BFieldElement.zero()
+assert a * a.inverse() == tf.BFieldElement.one()
+assert a ** 3 == tf.BFieldElement(27)
+assert int(-tf.BFieldElement.one()) == 2**64 - 2**32
+assert len({a, tf.BFieldElement(3)}) == 1
+try:
+    tf.BFieldElement(2**64 - 2**32 + 1)
+    assert False
+except ValueError:
+    pass
+try:
+    tf.BFieldElement.zero().inverse()
+    assert False
+except ZeroDivisionError:
+    pass
+"#,
+        );
+    }
+
+    #[test]
+    fn x_field_element() {
+        run_python(
+            r#"
+a, b = tf.BFieldElement(3), tf.BFieldElement(5)
+x = tf.XFieldElement([a, b, a])
+assert x * x.inverse() == tf.XFieldElement([tf.BFieldElement.one(), tf.BFieldElement.zero(), tf.BFieldElement.zero()])
+assert (x / x) * x == x
+"#,
+        );
+    }
+
+    #[test]
+    fn polynomial() {
+        run_python(
+            r#"
+a, zero, one = tf.BFieldElement(3), tf.BFieldElement.zero(), tf.BFieldElement.one()
+domain = [tf.BFieldElement(i) for i in range(1, 5)]
+values = [tf.BFieldElement(i * i) for i in range(1, 5)]
+p = tf.Polynomial.interpolate(domain, values)
+assert p.degree == 2
+assert p.evaluate(tf.BFieldElement(7)) == tf.BFieldElement(49)
+zerofier = tf.Polynomial.zerofier(domain)
+assert all(zerofier.evaluate(d) == zero for d in domain)
+q, r = p.divide(tf.Polynomial([a, one]))
+assert q * tf.Polynomial([a, one]) + r == p
+assert p // p == tf.Polynomial([one])
+assert p.formal_derivative().degree == 1
+assert len({tf.Polynomial([a, zero]), tf.Polynomial([a])}) == 1
+assert len({p - p, tf.Polynomial([]), tf.Polynomial([zero, zero])}) == 1
+"#,
+        );
+    }
+
+    #[test]
+    fn digest() {
+        run_python(
+            r#"
+digest = tf.Tip5.hash_varlen([tf.BFieldElement(3), tf.BFieldElement(5)])
+assert tf.Digest.from_hex(digest.to_hex()) == digest
+assert tf.Digest(digest.elements) == digest
+"#,
+        );
+    }
+
+    #[test]
+    fn tip5() {
+        run_python(
+            r#"
+sponge = tf.Tip5()
+sponge.pad_and_absorb_all([tf.BFieldElement(3), tf.BFieldElement(5)])
+assert len(sponge.squeeze()) == 10
+assert all(0 <= i < 16 for i in sponge.sample_indices(16, 10))
+assert len(sponge.sample_scalars(3)) == 3
+"#,
+        );
+    }
+
+    #[test]
+    fn merkle_tree() {
+        run_python(
+            r#"
+leafs = [tf.Tip5.hash_varlen([tf.BFieldElement(i)]) for i in range(8)]
+tree = tf.MerkleTree(leafs)
+assert tree.num_leafs == 8 and tree.height == 3
+assert tree.leaf(2) == leafs[2]
+assert tree.root == tf.Tip5.hash_pair(
+    tf.Tip5.hash_pair(tf.Tip5.hash_pair(leafs[0], leafs[1]), tf.Tip5.hash_pair(leafs[2], leafs[3])),
+    tf.Tip5.hash_pair(tf.Tip5.hash_pair(leafs[4], leafs[5]), tf.Tip5.hash_pair(leafs[6], leafs[7])),
+)
+structure = tree.authentication_structure([1, 6])
+assert tf.MerkleTree.verify(tree.root, 3, [1, 6], [leafs[1], leafs[6]], structure)
+assert not tf.MerkleTree.verify(tree.root, 3, [1, 6], [leafs[1], leafs[5]], structure)
+try:
+    tf.MerkleTree(leafs[:3])
+    assert False
+except ValueError:
+    pass
+"#,
+        );
+    }
+}
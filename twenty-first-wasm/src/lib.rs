@@ -0,0 +1,493 @@
+//! WebAssembly bindings for [twenty-first](twenty_first), exposing [Tip5] hashing, Merkle tree
+//! inclusion proof verification, and [Merkle Mountain Range](MmrAccumulator) accumulators to
+//! JavaScript and TypeScript.
+//!
+//! Field elements cross the boundary as `bigint`s, that is, as `u64`s in canonical
+//! representation. Lists of digests are passed as flat `BigUint64Array`s, with
+//! [`Digest::LEN`](twenty_first::prelude::Digest::LEN) consecutive elements per digest.
+//! Inputs containing non-canonical elements are rejected.
+//!
+//! Membership proofs of a Merkle Mountain Range are authentication paths, flattened the same
+//! way. They go stale whenever the accumulator changes; the [`MmrAccumulator`] provides methods
+//! to update them for appends and leaf mutations.
+//!
+//! The TypeScript typings are generated alongside the JavaScript glue code, for example by
+//! running `wasm-pack build --target web` in this crate's directory.
+//!
+//! [Tip5]: twenty_first::prelude::Tip5
+
+use std::marker::PhantomData;
+
+use thiserror::Error;
+use twenty_first::prelude::AlgebraicHasher;
+use twenty_first::prelude::BFieldElement;
+use twenty_first::prelude::MerkleTreeInclusionProof;
+use twenty_first::prelude::Mmr;
+use twenty_first::prelude::MmrMembershipProof;
+use twenty_first::prelude::Tip5;
+use twenty_first::util_types::mmr::mmr_accumulator;
+use twenty_first::util_types::mmr::mmr_trait::LeafMutation;
+use twenty_first::util_types::mmr::shared_basic;
+use wasm_bindgen::prelude::*;
+
+/// Why some input from JavaScript was rejected.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Error)]
+enum InputError {
+    #[error("non-canonical field element: {0}")]
+    NotCanonical(u64),
+
+    #[error(
+        "a digest consists of exactly {} elements",
+        twenty_first::prelude::Digest::LEN
+    )]
+    InvalidDigestLength,
+
+    #[error("number of peaks does not match number of leafs")]
+    PeakCountMismatch,
+
+    #[error("leaf index {0} is out of range")]
+    LeafIndexOutOfRange(u64),
+
+    #[error("authentication path has the wrong length")]
+    AuthenticationPathLength,
+}
+
+/// A [Tip5] digest.
+///
+/// [Tip5]: twenty_first::prelude::Tip5
+#[wasm_bindgen]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Digest(twenty_first::prelude::Digest);
+
+#[wasm_bindgen]
+impl Digest {
+    /// Construct a digest from its elements.
+    #[wasm_bindgen(constructor)]
+    pub fn new(elements: Vec<u64>) -> Result<Digest, JsError> {
+        Ok(try_digest(&elements)?)
+    }
+
+    #[wasm_bindgen(js_name = fromHex)]
+    pub fn from_hex(hex: &str) -> Result<Digest, JsError> {
+        Ok(Digest(twenty_first::prelude::Digest::try_from_hex(hex)?))
+    }
+
+    #[wasm_bindgen(js_name = toHex)]
+    pub fn to_hex(&self) -> String {
+        self.0.to_hex()
+    }
+
+    pub fn elements(&self) -> Vec<u64> {
+        self.0.values().map(|e| e.value()).to_vec()
+    }
+
+    pub fn equals(&self, other: &Digest) -> bool {
+        self == other
+    }
+}
+
+/// Hash a sequence of field elements of arbitrary length.
+#[wasm_bindgen(js_name = hashVarlen)]
+pub fn hash_varlen(input: Vec<u64>) -> Result<Digest, JsError> {
+    let input = try_bfield_elements(&input)?;
+    Ok(Digest(Tip5::hash_varlen(&input)))
+}
+
+/// Hash two digests, for example the two children of a Merkle tree node.
+#[wasm_bindgen(js_name = hashPair)]
+pub fn hash_pair(left: &Digest, right: &Digest) -> Digest {
+    Digest(Tip5::hash_pair(left.0, right.0))
+}
+
+/// Verify that the leafs are in the Merkle tree of the given height and root. The leafs are
+/// passed as flat digests, where the `i`th leaf has index `leaf_indices[i]`. The authentication
+/// structure is the one produced by `MerkleTree::authentication_structure`, flattened.
+///
+/// Returns `false` for any malformed input.
+#[wasm_bindgen(js_name = verifyMerkleInclusion)]
+pub fn verify_merkle_inclusion(
+    root: &Digest,
+    tree_height: usize,
+    leaf_indices: Vec<usize>,
+    leafs: Vec<u64>,
+    authentication_structure: Vec<u64>,
+) -> bool {
+    let Ok(leafs) = try_digests(&leafs) else {
+        return false;
+    };
+    let Ok(authentication_structure) = try_digests(&authentication_structure) else {
+        return false;
+    };
+    if leaf_indices.len() != leafs.len() {
+        return false;
+    }
+
+    let inclusion_proof = MerkleTreeInclusionProof::<Tip5> {
+        tree_height,
+        indexed_leafs: leaf_indices.into_iter().zip(leafs).collect(),
+        authentication_structure,
+        _hasher: PhantomData,
+    };
+    inclusion_proof.verify(root.0)
+}
+
+/// The accumulator of a Merkle Mountain Range: its peaks and its number of leafs.
+#[wasm_bindgen]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MmrAccumulator(mmr_accumulator::MmrAccumulator);
+
+#[wasm_bindgen]
+impl MmrAccumulator {
+    /// An accumulator for the empty Merkle Mountain Range.
+    #[wasm_bindgen(constructor)]
+    #[allow(clippy::new_without_default)]
+    pub fn new() -> MmrAccumulator {
+        MmrAccumulator(mmr_accumulator::MmrAccumulator::new(vec![]))
+    }
+
+    /// Restore an accumulator from its (flattened) peaks and its number of leafs.
+    #[wasm_bindgen(js_name = fromPeaks)]
+    pub fn from_peaks(peaks: Vec<u64>, num_leafs: u64) -> Result<MmrAccumulator, JsError> {
+        let peaks = try_digests(&peaks)?;
+        if peaks.len() != num_leafs.count_ones() as usize {
+            return Err(InputError::PeakCountMismatch.into());
+        }
+        let accumulator = mmr_accumulator::MmrAccumulator::init(peaks, num_leafs);
+        Ok(MmrAccumulator(accumulator))
+    }
+
+    /// The peaks, flattened.
+    pub fn peaks(&self) -> Vec<u64> {
+        flatten(&self.0.peaks())
+    }
+
+    #[wasm_bindgen(js_name = numLeafs)]
+    pub fn num_leafs(&self) -> u64 {
+        self.0.num_leafs()
+    }
+
+    /// A single digest committing to the entire Merkle Mountain Range.
+    #[wasm_bindgen(js_name = bagPeaks)]
+    pub fn bag_peaks(&self) -> Digest {
+        Digest(self.0.bag_peaks())
+    }
+
+    /// Append a leaf. Returns the leaf's authentication path, flattened.
+    pub fn append(&mut self, leaf: &Digest) -> Vec<u64> {
+        let membership_proof = self.0.append(leaf.0);
+        flatten(&membership_proof.authentication_path)
+    }
+
+    /// Verify that the leaf with the given index is in the Merkle Mountain Range, using the
+    /// leaf's (flattened) authentication path.
+    ///
+    /// Returns `false` for any malformed input.
+    #[wasm_bindgen(js_name = verifyMembership)]
+    pub fn verify_membership(
+        &self,
+        leaf_index: u64,
+        leaf: &Digest,
+        authentication_path: Vec<u64>,
+    ) -> bool {
+        let Ok(authentication_path) = try_digests(&authentication_path) else {
+            return false;
+        };
+        let membership_proof = MmrMembershipProof::new(authentication_path);
+        membership_proof.verify(leaf_index, leaf.0, &self.0.peaks(), self.0.num_leafs())
+    }
+
+    /// Replace the leaf with the given index, proving the old leaf's membership with its
+    /// (flattened) authentication path. The authentication path remains valid for the new leaf.
+    ///
+    /// Returns `false`, leaving the accumulator unchanged, if the authentication path does not
+    /// prove the old leaf's membership.
+    #[wasm_bindgen(js_name = mutateLeaf)]
+    pub fn mutate_leaf(
+        &mut self,
+        leaf_index: u64,
+        old_leaf: &Digest,
+        new_leaf: &Digest,
+        authentication_path: Vec<u64>,
+    ) -> bool {
+        if !self.verify_membership(leaf_index, old_leaf, authentication_path.clone()) {
+            return false;
+        }
+        let Ok(authentication_path) = try_digests(&authentication_path) else {
+            return false;
+        };
+        let membership_proof = MmrMembershipProof::new(authentication_path);
+        let leaf_mutation = LeafMutation::new(leaf_index, new_leaf.0, membership_proof);
+        self.0.mutate_leaf(leaf_mutation);
+        true
+    }
+
+    /// Update the (flattened) authentication path of the leaf with the given index such that it
+    /// remains valid once `new_leaf` is [appended](Self::append). Must be called _before_
+    /// appending.
+    #[wasm_bindgen(js_name = updateMembershipProofFromAppend)]
+    pub fn update_membership_proof_from_append(
+        &self,
+        leaf_index: u64,
+        authentication_path: Vec<u64>,
+        new_leaf: &Digest,
+    ) -> Result<Vec<u64>, JsError> {
+        let mut membership_proof = self.membership_proof(leaf_index, &authentication_path)?;
+        membership_proof.update_from_append(
+            leaf_index,
+            self.0.num_leafs(),
+            new_leaf.0,
+            &self.0.peaks(),
+        );
+        Ok(flatten(&membership_proof.authentication_path))
+    }
+
+    /// Update the (flattened) authentication path of the leaf with the given index such that it
+    /// remains valid once the leaf with index `mutated_leaf_index` is
+    /// [mutated](Self::mutate_leaf) to `new_leaf`. Must be called _before_ mutating.
+    #[wasm_bindgen(js_name = updateMembershipProofFromLeafMutation)]
+    pub fn update_membership_proof_from_leaf_mutation(
+        &self,
+        leaf_index: u64,
+        authentication_path: Vec<u64>,
+        mutated_leaf_index: u64,
+        new_leaf: &Digest,
+        mutated_leaf_authentication_path: Vec<u64>,
+    ) -> Result<Vec<u64>, JsError> {
+        let mut membership_proof = self.membership_proof(leaf_index, &authentication_path)?;
+        let mutated_leaf_membership_proof =
+            self.membership_proof(mutated_leaf_index, &mutated_leaf_authentication_path)?;
+        let leaf_mutation = LeafMutation::new(
+            mutated_leaf_index,
+            new_leaf.0,
+            mutated_leaf_membership_proof,
+        );
+        membership_proof.update_from_leaf_mutation(leaf_index, &leaf_mutation);
+        Ok(flatten(&membership_proof.authentication_path))
+    }
+
+    /// Parse an authentication path, checking that its shape fits the leaf index.
+    fn membership_proof(
+        &self,
+        leaf_index: u64,
+        authentication_path: &[u64],
+    ) -> Result<MmrMembershipProof, InputError> {
+        let num_leafs = self.0.num_leafs();
+        if leaf_index >= num_leafs {
+            return Err(InputError::LeafIndexOutOfRange(leaf_index));
+        }
+        let authentication_path = try_digests(authentication_path)?;
+        let (merkle_tree_index, _) =
+            shared_basic::leaf_index_to_mt_index_and_peak_index(leaf_index, num_leafs);
+        if authentication_path.len() != merkle_tree_index.ilog2() as usize {
+            return Err(InputError::AuthenticationPathLength);
+        }
+        Ok(MmrMembershipProof::new(authentication_path))
+    }
+}
+
+fn try_bfield_elements(elements: &[u64]) -> Result<Vec<BFieldElement>, InputError> {
+    elements
+        .iter()
+        .map(|&element| {
+            let bfe = BFieldElement::is_canonical(element).then(|| BFieldElement::new(element));
+            bfe.ok_or(InputError::NotCanonical(element))
+        })
+        .collect()
+}
+
+fn try_digest(elements: &[u64]) -> Result<Digest, InputError> {
+    let elements = try_bfield_elements(elements)?;
+    let digest = elements
+        .try_into()
+        .map_err(|_| InputError::InvalidDigestLength)?;
+    Ok(Digest(digest))
+}
+
+/// Split a flat list of elements into digests. A trailing, incomplete digest is an error.
+fn try_digests(elements: &[u64]) -> Result<Vec<twenty_first::prelude::Digest>, InputError> {
+    elements
+        .chunks(twenty_first::prelude::Digest::LEN)
+        .map(|chunk| try_digest(chunk).map(|digest| digest.0))
+        .collect()
+}
+
+fn flatten(digests: &[twenty_first::prelude::Digest]) -> Vec<u64> {
+    digests
+        .iter()
+        .flat_map(|digest| digest.values())
+        .map(|element| element.value())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use twenty_first::prelude::CpuParallel;
+    use twenty_first::prelude::MerkleTree;
+
+    use super::*;
+
+    fn digest(seed: u64) -> Digest {
+        Digest(Tip5::hash_varlen(&[BFieldElement::new(seed)]))
+    }
+
+    #[test]
+    fn digest_conversions_round_trip() {
+        let digest = digest(42);
+        assert_eq!(digest, Digest::new(digest.elements()).unwrap());
+        assert_eq!(digest, Digest::from_hex(&digest.to_hex()).unwrap());
+        assert!(digest.equals(&digest));
+    }
+
+    #[test]
+    fn malformed_digests_are_rejected() {
+        let non_canonical = u64::MAX;
+        assert_eq!(Err(InputError::InvalidDigestLength), try_digest(&[0; 4]));
+        let err = try_digest(&[0, 0, 0, 0, non_canonical]).unwrap_err();
+        assert_eq!(InputError::NotCanonical(non_canonical), err);
+        assert_eq!(Err(InputError::InvalidDigestLength), try_digests(&[0; 7]));
+    }
+
+    #[test]
+    fn hashing_agrees_with_tip5() {
+        let input = vec![1, 2, 3];
+        let expected = Tip5::hash_varlen(&try_bfield_elements(&input).unwrap());
+        assert_eq!(expected, hash_varlen(input).unwrap().0);
+
+        let (left, right) = (digest(0), digest(1));
+        assert_eq!(Tip5::hash_pair(left.0, right.0), hash_pair(&left, &right).0);
+    }
+
+    #[test]
+    fn merkle_inclusion_proof_verifies() {
+        let leafs = (0..8).map(|i| digest(i).0).collect::<Vec<_>>();
+        let tree = MerkleTree::<Tip5>::new::<CpuParallel>(&leafs).unwrap();
+        let root = Digest(tree.root());
+
+        let leaf_indices = vec![1, 6];
+        let opened_leafs = flatten(&[leafs[1], leafs[6]]);
+        let authentication_structure = flatten(&tree.authentication_structure(&[1, 6]).unwrap());
+        assert!(verify_merkle_inclusion(
+            &root,
+            3,
+            leaf_indices.clone(),
+            opened_leafs.clone(),
+            authentication_structure.clone(),
+        ));
+
+        let wrong_leafs = flatten(&[leafs[1], leafs[5]]);
+        assert!(!verify_merkle_inclusion(
+            &root,
+            3,
+            leaf_indices.clone(),
+            wrong_leafs,
+            authentication_structure.clone(),
+        ));
+
+        let truncated_structure = authentication_structure[1..].to_vec();
+        assert!(!verify_merkle_inclusion(
+            &root,
+            3,
+            leaf_indices,
+            opened_leafs,
+            truncated_structure,
+        ));
+    }
+
+    #[test]
+    fn mmr_accumulator_appends_and_verifies_membership() {
+        let mut accumulator = MmrAccumulator::new();
+        let authentication_paths = (0..5)
+            .map(|i| accumulator.append(&digest(i)))
+            .collect::<Vec<_>>();
+        assert_eq!(5, accumulator.num_leafs());
+
+        let last_leaf_index = 4;
+        let last_path = authentication_paths[last_leaf_index as usize].clone();
+        assert!(accumulator.verify_membership(last_leaf_index, &digest(4), last_path.clone()));
+        assert!(!accumulator.verify_membership(last_leaf_index, &digest(5), last_path));
+
+        let restored = MmrAccumulator::from_peaks(accumulator.peaks(), 5).unwrap();
+        assert_eq!(accumulator, restored);
+        assert_eq!(accumulator.bag_peaks(), restored.bag_peaks());
+    }
+
+    /// Append the leaf, keeping the authentication paths of all other leafs up to date.
+    fn append_and_update(
+        accumulator: &mut MmrAccumulator,
+        paths: &mut Vec<Vec<u64>>,
+        leaf: Digest,
+    ) {
+        for (leaf_index, path) in (0..).zip(paths.iter_mut()) {
+            *path = accumulator
+                .update_membership_proof_from_append(leaf_index, path.clone(), &leaf)
+                .unwrap();
+        }
+        paths.push(accumulator.append(&leaf));
+    }
+
+    #[test]
+    fn mmr_leaf_mutation_requires_valid_membership_proof() {
+        let mut accumulator = MmrAccumulator::new();
+        let mut authentication_paths = vec![];
+        for i in 0..5 {
+            append_and_update(&mut accumulator, &mut authentication_paths, digest(i));
+        }
+        let path = authentication_paths[1].clone();
+        let unchanged_accumulator = accumulator.clone();
+
+        assert!(!accumulator.mutate_leaf(1, &digest(2), &digest(42), path.clone()));
+        assert!(!accumulator.mutate_leaf(5, &digest(1), &digest(42), path.clone()));
+        assert_eq!(unchanged_accumulator, accumulator);
+
+        assert!(accumulator.mutate_leaf(1, &digest(1), &digest(42), path.clone()));
+        assert!(accumulator.verify_membership(1, &digest(42), path.clone()));
+        assert!(!accumulator.verify_membership(1, &digest(1), path));
+    }
+
+    #[test]
+    fn mmr_membership_proofs_can_be_kept_up_to_date() {
+        let mut accumulator = MmrAccumulator::new();
+        let mut authentication_paths = vec![];
+        let mut leafs = (0..6).map(digest).collect::<Vec<_>>();
+        for &leaf in &leafs {
+            append_and_update(&mut accumulator, &mut authentication_paths, leaf);
+        }
+
+        let (mutated_leaf_index, mutated_leaf) = (2, digest(42));
+        let mutated_leaf_path = authentication_paths[2].clone();
+        for (leaf_index, path) in (0..).zip(&mut authentication_paths) {
+            *path = accumulator
+                .update_membership_proof_from_leaf_mutation(
+                    leaf_index,
+                    path.clone(),
+                    mutated_leaf_index,
+                    &mutated_leaf,
+                    mutated_leaf_path.clone(),
+                )
+                .unwrap();
+        }
+        let old_leaf = leafs[2];
+        assert!(accumulator.mutate_leaf(2, &old_leaf, &mutated_leaf, mutated_leaf_path));
+        leafs[2] = mutated_leaf;
+
+        for (leaf_index, (leaf, path)) in (0..).zip(leafs.iter().zip(authentication_paths)) {
+            assert!(accumulator.verify_membership(leaf_index, leaf, path));
+        }
+    }
+
+    #[test]
+    fn malformed_membership_proofs_are_rejected() {
+        let mut accumulator = MmrAccumulator::new();
+        let mut authentication_paths = vec![];
+        for i in 0..4 {
+            append_and_update(&mut accumulator, &mut authentication_paths, digest(i));
+        }
+        let path = authentication_paths[3].clone();
+
+        let err = accumulator.membership_proof(4, &path).unwrap_err();
+        assert_eq!(InputError::LeafIndexOutOfRange(4), err);
+        let err = accumulator.membership_proof(3, &path[5..]).unwrap_err();
+        assert_eq!(InputError::AuthenticationPathLength, err);
+        assert!(accumulator.membership_proof(3, &path).is_ok());
+    }
+}
@@ -8,6 +8,9 @@ use crate::prelude::tip5::Digest;
 use crate::prelude::x_field_element::EXTENSION_DEGREE;
 use crate::prelude::BFieldElement;
 pub use crate::util_types::merkle_tree::MerkleTreeError;
+pub use crate::util_types::secret_sharing::SecretSharingError;
+pub use crate::util_types::security_level::SecurityLevelError;
+pub use crate::util_types::xmss::XmssError;
 
 #[derive(Debug, Clone, Eq, PartialEq, Error)]
 #[non_exhaustive]
@@ -49,6 +52,13 @@ pub enum TryFromDigestError {
     Overflow,
 }
 
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Error)]
+#[non_exhaustive]
+pub enum TryFromKemBytesError {
+    #[error("non-canonical {0} >= {} == `BFieldElement::P`", BFieldElement::P)]
+    NotCanonical(u64),
+}
+
 #[derive(Debug, Clone, PartialEq, Error)]
 #[non_exhaustive]
 pub enum TryFromHexDigestError {
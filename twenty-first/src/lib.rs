@@ -67,6 +67,20 @@ pub(crate) mod tests {
         implements_usual_auto_traits::<mock::mmr::MockMmr>();
         implements_usual_auto_traits::<util_types::algebraic_hasher::Domain>();
         implements_usual_auto_traits::<util_types::mmr::mmr_accumulator::MmrAccumulator>();
+        implements_usual_auto_traits::<util_types::transcript::Transcript<Tip5>>();
+        implements_usual_auto_traits::<util_types::wots::SecretKey>();
+        implements_usual_auto_traits::<util_types::wots::PublicKey>();
+        implements_usual_auto_traits::<util_types::wots::Signature>();
+        implements_usual_auto_traits::<util_types::xmss::SecretKey>();
+        implements_usual_auto_traits::<util_types::xmss::PublicKey>();
+        implements_usual_auto_traits::<util_types::xmss::Signature>();
+        implements_usual_auto_traits::<util_types::beacon::Output>();
+        implements_usual_auto_traits::<util_types::commitment::Opening>();
+        implements_usual_auto_traits::<util_types::secret_sharing::Share>();
+        implements_usual_auto_traits::<util_types::secret_sharing::Commitment>();
+        implements_usual_auto_traits::<util_types::secret_sharing::CommittedShare>();
+        implements_usual_auto_traits::<util_types::security_level::Parameters>();
+        implements_usual_auto_traits::<util_types::security_level::SecurityLevel>();
         implements_usual_auto_traits::<math::zerofier_tree::Branch<BFieldElement>>();
         implements_usual_auto_traits::<math::zerofier_tree::Leaf<BFieldElement>>();
         implements_usual_auto_traits::<math::zerofier_tree::ZerofierTree<BFieldElement>>();
@@ -83,7 +97,11 @@ pub(crate) mod tests {
         implements_usual_auto_traits::<error::ParseBFieldElementError>();
         implements_usual_auto_traits::<error::TryFromDigestError>();
         implements_usual_auto_traits::<error::TryFromHexDigestError>();
+        implements_usual_auto_traits::<error::TryFromKemBytesError>();
         implements_usual_auto_traits::<error::TryFromU32sError>();
         implements_usual_auto_traits::<error::TryFromXFieldElementError>();
+        implements_usual_auto_traits::<error::SecretSharingError>();
+        implements_usual_auto_traits::<error::SecurityLevelError>();
+        implements_usual_auto_traits::<error::XmssError>();
     }
 }
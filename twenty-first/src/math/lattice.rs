@@ -1,10 +1,21 @@
+//! Arithmetic in the cyclotomic ring `Fp[X] / (X^64 + 1)` and in modules over it, as well as a
+//! key encapsulation mechanism built on top.
+//!
+//! The ring [`CyclotomicRingElement`] and the modules [`ModuleElement`] are general-purpose and
+//! can serve as the foundation for other lattice-based constructions.
+
 use std::ops::Add;
 use std::ops::AddAssign;
 use std::ops::Mul;
+use std::ops::MulAssign;
+use std::ops::Neg;
 use std::ops::Sub;
+use std::ops::SubAssign;
 
 use itertools::Itertools;
+use num_traits::ConstOne;
 use num_traits::ConstZero;
+use num_traits::One;
 use num_traits::Zero;
 use rayon::prelude::IntoParallelIterator;
 use rayon::prelude::ParallelIterator;
@@ -13,7 +24,9 @@ use serde_derive::Deserialize;
 use serde_derive::Serialize;
 
 use super::b_field_element::BFieldElement;
+use super::traits::FiniteField;
 
+/// The inverse of [`coset_ntt_noswap_64`].
 pub fn coset_intt_noswap_64(array: &mut [BFieldElement; 64]) {
     const N: usize = 64;
     const N_INV: BFieldElement = BFieldElement::new(18158513693329981441);
@@ -110,6 +123,9 @@ pub fn coset_intt_noswap_64(array: &mut [BFieldElement; 64]) {
     }
 }
 
+/// In-place negacyclic number-theoretic transform of the coefficients of a polynomial of degree
+/// less than 64, _i.e._, evaluation on the roots of `X^64 + 1`. The output is in bit-reversed
+/// order, which is irrelevant for point-wise multiplication.
 pub fn coset_ntt_noswap_64(array: &mut [BFieldElement; 64]) {
     const N: usize = 64;
 
@@ -202,6 +218,12 @@ pub fn coset_ntt_noswap_64(array: &mut [BFieldElement; 64]) {
 
 pub const CYCLOTOMIC_RING_ELEMENT_SIZE_IN_BFES: usize = 64;
 
+/// An element of the cyclotomic ring `Fp[X] / (X^64 + 1)`, where `p` is the
+/// [`BFieldElement`]'s prime. Represented by its 64 coefficients, the constant term first.
+///
+/// Since `X^64 + 1` splits into linear factors over `Fp`, multiplication is performed in the
+/// [NTT domain](Self::ntt). Repeated products of the same operands are cheaper if the operands
+/// are transformed once and multiplied [point-wise](Self::hadamard).
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct CyclotomicRingElement {
     #[serde(with = "BigArray")]
@@ -223,6 +245,13 @@ impl From<CyclotomicRingElement> for [BFieldElement; CYCLOTOMIC_RING_ELEMENT_SIZ
 }
 
 impl CyclotomicRingElement {
+    /// The coefficients, the constant term first.
+    pub fn coefficients(&self) -> [BFieldElement; CYCLOTOMIC_RING_ELEMENT_SIZE_IN_BFES] {
+        self.coefficients
+    }
+
+    /// Sample an element with short coefficients from (at least) 512 bytes of randomness. See
+    /// [`sample_short_bfield_element`].
     pub fn sample_short(randomness: &[u8]) -> CyclotomicRingElement {
         debug_assert!(randomness.len() >= 8 * 64);
         CyclotomicRingElement {
@@ -236,6 +265,8 @@ impl CyclotomicRingElement {
         }
     }
 
+    /// Sample an element with (nearly) uniformly distributed coefficients from (at least) 576
+    /// bytes of randomness.
     pub fn sample_uniform(randomness: &[u8]) -> CyclotomicRingElement {
         debug_assert!(randomness.len() >= 9 * 64);
         let mut coefficients = [BFieldElement::ZERO; 64];
@@ -250,6 +281,8 @@ impl CyclotomicRingElement {
         CyclotomicRingElement { coefficients }
     }
 
+    /// Coefficient-wise product. For elements in the [NTT domain](Self::ntt), this corresponds to
+    /// the ring product.
     pub fn hadamard(a: CyclotomicRingElement, b: CyclotomicRingElement) -> CyclotomicRingElement {
         let mut c = CyclotomicRingElement::zero();
         for i in 0..64 {
@@ -257,6 +290,35 @@ impl CyclotomicRingElement {
         }
         c
     }
+
+    /// Transform into the NTT domain. See [`coset_ntt_noswap_64`].
+    pub fn ntt(&self) -> Self {
+        let mut coefficients = self.coefficients;
+        coset_ntt_noswap_64(&mut coefficients);
+        Self { coefficients }
+    }
+
+    /// Transform back from the NTT domain. The inverse of [`ntt`](Self::ntt).
+    pub fn intt(&self) -> Self {
+        let mut coefficients = self.coefficients;
+        coset_intt_noswap_64(&mut coefficients);
+        Self { coefficients }
+    }
+
+    /// The multiplicative inverse, if it exists. An element is invertible if and only if none of
+    /// its [NTT-domain](Self::ntt) values is zero, _i.e._, if it is coprime to `X^64 + 1`.
+    pub fn inverse(&self) -> Option<Self> {
+        let evaluations = self.ntt().coefficients;
+        if evaluations.iter().any(|e| e.is_zero()) {
+            return None;
+        }
+
+        let inverse_evaluations = BFieldElement::batch_inversion(evaluations.to_vec());
+        let inverse = Self {
+            coefficients: inverse_evaluations.try_into().unwrap(),
+        };
+        Some(inverse.intt())
+    }
 }
 
 impl Add for CyclotomicRingElement {
@@ -282,6 +344,16 @@ impl AddAssign for CyclotomicRingElement {
     }
 }
 
+impl Neg for CyclotomicRingElement {
+    type Output = CyclotomicRingElement;
+
+    fn neg(self) -> Self::Output {
+        CyclotomicRingElement {
+            coefficients: self.coefficients.map(|c| -c),
+        }
+    }
+}
+
 impl Sub for CyclotomicRingElement {
     type Output = CyclotomicRingElement;
 
@@ -296,6 +368,15 @@ impl Sub for CyclotomicRingElement {
     }
 }
 
+impl SubAssign for CyclotomicRingElement {
+    fn sub_assign(&mut self, rhs: Self) {
+        self.coefficients
+            .iter_mut()
+            .zip(rhs.coefficients.iter())
+            .for_each(|(l, r)| *l -= *r);
+    }
+}
+
 impl Mul for CyclotomicRingElement {
     type Output = CyclotomicRingElement;
 
@@ -318,6 +399,22 @@ impl Mul for CyclotomicRingElement {
     }
 }
 
+impl MulAssign for CyclotomicRingElement {
+    fn mul_assign(&mut self, rhs: Self) {
+        *self = *self * rhs;
+    }
+}
+
+impl Mul<BFieldElement> for CyclotomicRingElement {
+    type Output = CyclotomicRingElement;
+
+    fn mul(self, rhs: BFieldElement) -> Self::Output {
+        CyclotomicRingElement {
+            coefficients: self.coefficients.map(|c| c * rhs),
+        }
+    }
+}
+
 impl Zero for CyclotomicRingElement {
     fn zero() -> Self {
         CyclotomicRingElement {
@@ -330,6 +427,18 @@ impl Zero for CyclotomicRingElement {
     }
 }
 
+impl One for CyclotomicRingElement {
+    fn one() -> Self {
+        let mut coefficients = [BFieldElement::ZERO; 64];
+        coefficients[0] = BFieldElement::ONE;
+        CyclotomicRingElement { coefficients }
+    }
+
+    fn is_one(&self) -> bool {
+        *self == Self::one()
+    }
+}
+
 pub fn embed_msg(msg: [u8; 32]) -> CyclotomicRingElement {
     let mut embedding: [BFieldElement; 64] = [BFieldElement::ZERO; 64];
     for i in 0..msg.len() {
@@ -352,71 +461,51 @@ pub fn embed_msg(msg: [u8; 32]) -> CyclotomicRingElement {
     }
 }
 
+/// Extract the message embedded with [`embed_msg`]. Does not branch on (secret) data.
 pub fn extract_msg(embedding: CyclotomicRingElement) -> [u8; 32] {
     let mut msg = [0u8; 32];
     for (ctr, pair) in embedding.coefficients.chunks(2).enumerate() {
         let mut byte = 0u8;
         let mut value = pair[0].value();
         for j in 0..4 {
-            let chunk = value & 0xffff;
+            byte |= extract_bit(value & 0xffff) << j;
             value >>= 16;
-
-            let bit = if chunk < (1 << 14) || (1 << 16) - chunk < (1 << 14) {
-                0
-            } else {
-                1
-            };
-            byte |= bit << j;
         }
 
         value = pair[1].value();
         for j in 0..4 {
-            let chunk = value & 0xffff;
+            byte |= extract_bit(value & 0xffff) << (4 + j);
             value >>= 16;
-
-            let bit = if chunk < (1 << 14) || (1 << 16) - chunk < (1 << 14) {
-                0
-            } else {
-                1
-            };
-            byte |= bit << (4 + j);
         }
         msg[ctr] = byte;
     }
     msg
 }
 
-const fn num_set_bits(a: u8) -> u8 {
-    let mut sum = 0;
-    let mut i = 0;
-    while i < 8 {
-        let bit = if a & (1 << i) != 0 { 1 } else { 0 };
-        sum += bit;
-        i += 1;
+/// Decode a single 16-bit chunk: 0 if the chunk is close to 0 (modulo 2^16), 1 otherwise.
+/// Equivalent to `chunk < 2^14 || 2^16 - chunk < 2^14`, but without branching.
+const fn extract_bit(chunk: u64) -> u8 {
+    // For values less than 2^63, the top bit of the difference indicates `lhs < rhs`.
+    const fn is_less_than(lhs: u64, rhs: u64) -> u64 {
+        lhs.wrapping_sub(rhs) >> 63
     }
-    sum
-}
 
-const fn num_set_bits_table() -> [u8; 256] {
-    let mut table: [u8; 256] = [0u8; 256];
-    let mut i = 1;
-    while i < 256 {
-        table[i] = num_set_bits(i as u8);
-        i += 1;
-    }
-    table
+    let is_close_to_zero = is_less_than(chunk, 1 << 14) | is_less_than((1 << 16) - chunk, 1 << 14);
+    (is_close_to_zero ^ 1) as u8
 }
 
+/// Sample a short field element from 8 bytes of randomness. Does not branch on or index with
+/// the randomness.
 pub fn sample_short_bfield_element(randomness: &[u8; 8]) -> BFieldElement {
-    const NUM_SET_BITS: [u8; 256] = num_set_bits_table();
-    let left = ((NUM_SET_BITS[randomness[0] as usize] as u64) << (3 * 16))
-        + ((NUM_SET_BITS[randomness[1] as usize] as u64) << (2 * 16))
-        + ((NUM_SET_BITS[randomness[2] as usize] as u64) << 16)
-        + (NUM_SET_BITS[randomness[3] as usize] as u64);
-    let right = ((NUM_SET_BITS[randomness[4] as usize] as u64) << (3 * 16))
-        + ((NUM_SET_BITS[randomness[5] as usize] as u64) << (2 * 16))
-        + ((NUM_SET_BITS[randomness[6] as usize] as u64) << 16)
-        + (NUM_SET_BITS[randomness[7] as usize] as u64);
+    let num_set_bits = |i: usize| randomness[i].count_ones() as u64;
+    let left = (num_set_bits(0) << (3 * 16))
+        + (num_set_bits(1) << (2 * 16))
+        + (num_set_bits(2) << 16)
+        + num_set_bits(3);
+    let right = (num_set_bits(4) << (3 * 16))
+        + (num_set_bits(5) << (2 * 16))
+        + (num_set_bits(6) << 16)
+        + num_set_bits(7);
     BFieldElement::new(left) - BFieldElement::new(right)
 }
 
@@ -429,7 +518,24 @@ pub struct ModuleElement<const N: usize> {
     elements: [CyclotomicRingElement; N],
 }
 
+impl<const N: usize> From<[CyclotomicRingElement; N]> for ModuleElement<N> {
+    fn from(elements: [CyclotomicRingElement; N]) -> Self {
+        Self { elements }
+    }
+}
+
+impl<const N: usize> From<ModuleElement<N>> for [CyclotomicRingElement; N] {
+    fn from(value: ModuleElement<N>) -> Self {
+        value.elements
+    }
+}
+
 impl<const N: usize> ModuleElement<N> {
+    /// The cyclotomic ring elements, in row-major order.
+    pub fn elements(&self) -> [CyclotomicRingElement; N] {
+        self.elements
+    }
+
     pub fn sample_short(randomness: &[u8]) -> Self {
         debug_assert!(randomness.len() >= 8 * 64 * N);
         let mut elements = [CyclotomicRingElement::zero(); N];
@@ -639,6 +745,7 @@ pub mod kem {
     use sha3::Sha3_256;
     use sha3::Shake256;
 
+    use crate::error::TryFromKemBytesError;
     use crate::math::b_field_element::BFieldElement;
 
     use super::embed_msg;
@@ -667,6 +774,19 @@ pub mod kem {
 
     pub const CIPHERTEXT_SIZE_IN_BFES: usize = CYCLOTOMIC_RING_ELEMENT_SIZE_IN_BFES * 5;
 
+    /// The size of the canonical byte encoding of a [`Ciphertext`].
+    pub const CIPHERTEXT_SIZE_IN_BYTES: usize = CIPHERTEXT_SIZE_IN_BFES * BFieldElement::BYTES;
+
+    const SEED_SIZE_IN_BYTES: usize = 32;
+    const PUBLIC_KEY_SIZE_IN_BFES: usize = CYCLOTOMIC_RING_ELEMENT_SIZE_IN_BFES * 4;
+
+    /// The size of the canonical byte encoding of a [`PublicKey`].
+    pub const PUBLIC_KEY_SIZE_IN_BYTES: usize =
+        SEED_SIZE_IN_BYTES + PUBLIC_KEY_SIZE_IN_BFES * BFieldElement::BYTES;
+
+    /// The size of the byte encoding of a [`SecretKey`].
+    pub const SECRET_KEY_SIZE_IN_BYTES: usize = 2 * SEED_SIZE_IN_BYTES;
+
     impl From<[BFieldElement; CIPHERTEXT_SIZE_IN_BFES]> for Ciphertext {
         fn from(value: [BFieldElement; CIPHERTEXT_SIZE_IN_BFES]) -> Self {
             let (bg_slice, bga_m_slice) = value.split_at(4 * CYCLOTOMIC_RING_ELEMENT_SIZE_IN_BFES);
@@ -720,6 +840,99 @@ pub mod kem {
         }
     }
 
+    impl From<Ciphertext> for [u8; CIPHERTEXT_SIZE_IN_BYTES] {
+        fn from(value: Ciphertext) -> Self {
+            let bfes: [BFieldElement; CIPHERTEXT_SIZE_IN_BFES] = value.into();
+            bfes_to_bytes(&bfes).try_into().unwrap()
+        }
+    }
+
+    impl TryFrom<[u8; CIPHERTEXT_SIZE_IN_BYTES]> for Ciphertext {
+        type Error = TryFromKemBytesError;
+
+        fn try_from(value: [u8; CIPHERTEXT_SIZE_IN_BYTES]) -> Result<Self, Self::Error> {
+            let bfes: [BFieldElement; CIPHERTEXT_SIZE_IN_BFES] =
+                bytes_to_bfes(&value)?.try_into().unwrap();
+            Ok(bfes.into())
+        }
+    }
+
+    impl From<PublicKey> for [u8; PUBLIC_KEY_SIZE_IN_BYTES] {
+        fn from(value: PublicKey) -> Self {
+            let ga = value
+                .ga
+                .elements
+                .iter()
+                .flat_map(|e| e.coefficients)
+                .collect_vec();
+            [value.seed.to_vec(), bfes_to_bytes(&ga)]
+                .concat()
+                .try_into()
+                .unwrap()
+        }
+    }
+
+    impl TryFrom<[u8; PUBLIC_KEY_SIZE_IN_BYTES]> for PublicKey {
+        type Error = TryFromKemBytesError;
+
+        fn try_from(value: [u8; PUBLIC_KEY_SIZE_IN_BYTES]) -> Result<Self, Self::Error> {
+            let (seed, ga) = value.split_at(SEED_SIZE_IN_BYTES);
+            let ga = bytes_to_bfes(ga)?;
+            let elements = ga
+                .chunks(CYCLOTOMIC_RING_ELEMENT_SIZE_IN_BFES)
+                .map(|chunk| {
+                    let coefficients: [_; CYCLOTOMIC_RING_ELEMENT_SIZE_IN_BFES] =
+                        chunk.try_into().unwrap();
+                    CyclotomicRingElement::from(coefficients)
+                })
+                .collect_vec()
+                .try_into()
+                .unwrap();
+
+            Ok(Self {
+                seed: seed.try_into().unwrap(),
+                ga: ModuleElement { elements },
+            })
+        }
+    }
+
+    impl From<SecretKey> for [u8; SECRET_KEY_SIZE_IN_BYTES] {
+        fn from(value: SecretKey) -> Self {
+            [value.key, value.seed].concat().try_into().unwrap()
+        }
+    }
+
+    impl From<[u8; SECRET_KEY_SIZE_IN_BYTES]> for SecretKey {
+        fn from(value: [u8; SECRET_KEY_SIZE_IN_BYTES]) -> Self {
+            let (key, seed) = value.split_at(SEED_SIZE_IN_BYTES);
+            Self {
+                key: key.try_into().unwrap(),
+                seed: seed.try_into().unwrap(),
+            }
+        }
+    }
+
+    /// The canonical, little-endian byte encoding of the given field elements.
+    fn bfes_to_bytes(bfes: &[BFieldElement]) -> Vec<u8> {
+        bfes.iter()
+            .flat_map(|&bfe| <[u8; BFieldElement::BYTES]>::from(bfe))
+            .collect()
+    }
+
+    /// Decode field elements from their canonical byte encoding, rejecting non-canonical
+    /// encodings.
+    fn bytes_to_bfes(bytes: &[u8]) -> Result<Vec<BFieldElement>, TryFromKemBytesError> {
+        debug_assert_eq!(0, bytes.len() % BFieldElement::BYTES);
+        bytes
+            .chunks(BFieldElement::BYTES)
+            .map(|chunk| u64::from_le_bytes(chunk.try_into().unwrap()))
+            .map(|value| {
+                let bfe = BFieldElement::is_canonical(value).then(|| BFieldElement::new(value));
+                bfe.ok_or(TryFromKemBytesError::NotCanonical(value))
+            })
+            .collect()
+    }
+
     /// randomness extension
     pub(super) fn shake256<const NUM_OUT_BYTES: usize>(
         randomness: impl AsRef<[u8]>,
@@ -811,6 +1024,57 @@ pub mod kem {
         let shared_key = Sha3_256::digest(payload).into();
         Some(shared_key)
     }
+
+    /// Decapsulate with implicit rejection: use the secret key to extract the
+    /// corresponding shared symmetric key from a ciphertext. If the ciphertext is
+    /// invalid, return a pseudorandom key derived from the secret key and the
+    /// ciphertext instead, which is indistinguishable from a valid shared key to
+    /// anyone not knowing the secret key.
+    ///
+    /// Unlike [`dec`], this function neither signals invalid ciphertexts nor
+    /// branches on secret data, making it suitable for deployment.
+    pub fn dec_implicit_rejection(sk: SecretKey, ctxt: Ciphertext) -> [u8; 32] {
+        let (a, _) = derive_secret_vectors(&sk.key);
+        let bga = ModuleElement::<3>::multiply_hadamard::<1, 4, 1, 4, 4, 1>(ctxt.bg, a.ntt());
+        let m = (ctxt.bga_m - bga).intt();
+        let payload = extract_msg(m.elements[0]);
+
+        let pk = derive_public_key(&sk.key, &sk.seed);
+        let regenerated_ciphertext = generate_ciphertext_derandomized(pk, payload);
+
+        let ctxt_bytes: [u8; CIPHERTEXT_SIZE_IN_BYTES] = ctxt.into();
+        let regenerated_bytes: [u8; CIPHERTEXT_SIZE_IN_BYTES] = regenerated_ciphertext.into();
+        let ciphertexts_are_equal = constant_time_eq(&ctxt_bytes, &regenerated_bytes);
+
+        let shared_key: [u8; 32] = Sha3_256::digest(payload).into();
+        let rejection_secret: [u8; SEED_SIZE_IN_BYTES] =
+            shake256([sk.key.to_vec(), vec![2u8]].concat());
+        let rejection_key: [u8; 32] =
+            Sha3_256::digest([rejection_secret.to_vec(), ctxt_bytes.to_vec()].concat()).into();
+
+        constant_time_select(ciphertexts_are_equal, shared_key, rejection_key)
+    }
+
+    /// Returns 1 if the slices are equal, and 0 otherwise. The running time depends
+    /// only on the length of the slices, not on their content.
+    fn constant_time_eq(lhs: &[u8], rhs: &[u8]) -> u8 {
+        debug_assert_eq!(lhs.len(), rhs.len());
+        let difference = lhs.iter().zip(rhs).fold(0u8, |acc, (&l, &r)| acc | (l ^ r));
+
+        // maps 0 to 1 and every other value to 0
+        ((u16::from(difference).wrapping_sub(1) >> 8) & 1) as u8
+    }
+
+    /// Returns `lhs` if `choice` is 1, and `rhs` if `choice` is 0, without branching.
+    fn constant_time_select<const N: usize>(choice: u8, lhs: [u8; N], rhs: [u8; N]) -> [u8; N] {
+        debug_assert!(choice <= 1);
+        let mask = choice.wrapping_neg();
+        let mut selected = [0u8; N];
+        for i in 0..N {
+            selected[i] = (lhs[i] & mask) | (rhs[i] & !mask);
+        }
+        selected
+    }
 }
 
 #[cfg(test)]
@@ -824,6 +1088,7 @@ mod lattice_test {
     use sha3::Digest as Sha3Digest;
     use sha3::Sha3_256;
 
+    use crate::error::TryFromKemBytesError;
     use crate::math::b_field_element::BFieldElement;
     use crate::math::lattice::kem::Ciphertext;
     use crate::math::lattice::kem::PublicKey;
@@ -871,6 +1136,105 @@ mod lattice_test {
         assert_eq!(c_fast, c_schoolbook);
     }
 
+    fn random_ring_element() -> CyclotomicRingElement {
+        CyclotomicRingElement::from(random::<[BFieldElement; 64]>())
+    }
+
+    #[test]
+    fn ring_arithmetic_satisfies_ring_axioms() {
+        let a = random_ring_element();
+        let b = random_ring_element();
+        let c = random_ring_element();
+        let zero = CyclotomicRingElement::zero();
+        let one = CyclotomicRingElement::one();
+
+        assert_eq!(a + b, b + a);
+        assert_eq!(a * b, b * a);
+        assert_eq!((a + b) + c, a + (b + c));
+        assert_eq!((a * b) * c, a * (b * c));
+        assert_eq!(a * (b + c), a * b + a * c);
+        assert_eq!(a, a + zero);
+        assert_eq!(a, a * one);
+        assert_eq!(zero, a + -a);
+        assert_eq!(a - b, a + -b);
+    }
+
+    #[test]
+    fn ring_assign_operators_agree_with_binary_operators() {
+        let a = random_ring_element();
+        let b = random_ring_element();
+
+        let mut sum = a;
+        sum += b;
+        assert_eq!(a + b, sum);
+
+        let mut difference = a;
+        difference -= b;
+        assert_eq!(a - b, difference);
+
+        let mut product = a;
+        product *= b;
+        assert_eq!(a * b, product);
+    }
+
+    #[test]
+    fn x_to_the_64_is_minus_one() {
+        let mut x_coefficients = [BFieldElement::ZERO; 64];
+        x_coefficients[1] = BFieldElement::ONE;
+        let x = CyclotomicRingElement::from(x_coefficients);
+
+        let x_to_the_64 = (0..6).fold(x, |acc, _| acc * acc);
+        assert_eq!(-CyclotomicRingElement::one(), x_to_the_64);
+    }
+
+    #[test]
+    fn scalar_multiplication_agrees_with_ring_multiplication() {
+        let a = random_ring_element();
+        let scalar: BFieldElement = random();
+        let mut scalar_coefficients = [BFieldElement::ZERO; 64];
+        scalar_coefficients[0] = scalar;
+        let scalar_as_ring_element = CyclotomicRingElement::from(scalar_coefficients);
+
+        assert_eq!(a * scalar_as_ring_element, a * scalar);
+    }
+
+    #[test]
+    fn ntt_domain_product_agrees_with_ring_product() {
+        let a = random_ring_element();
+        let b = random_ring_element();
+        assert_eq!(a, a.ntt().intt());
+        assert_eq!(
+            a * b,
+            CyclotomicRingElement::hadamard(a.ntt(), b.ntt()).intt()
+        );
+    }
+
+    #[test]
+    fn random_ring_element_has_inverse() {
+        let a = random_ring_element();
+        let a_inv = a.inverse().unwrap();
+        assert!((a * a_inv).is_one());
+        assert_eq!(a, a_inv.inverse().unwrap());
+    }
+
+    #[test]
+    fn non_units_have_no_inverse() {
+        assert!(CyclotomicRingElement::zero().inverse().is_none());
+
+        let mut evaluations = random_ring_element().ntt().coefficients();
+        evaluations[17] = BFieldElement::ZERO;
+        let zero_divisor = CyclotomicRingElement::from(evaluations).intt();
+        assert!(zero_divisor.inverse().is_none());
+    }
+
+    #[test]
+    fn module_element_conversion() {
+        let elements = [random_ring_element(), random_ring_element()];
+        let module_element = ModuleElement::from(elements);
+        assert_eq!(elements, module_element.elements());
+        assert_eq!(elements, <[CyclotomicRingElement; 2]>::from(module_element));
+    }
+
     #[test]
     fn test_embedding() {
         let mut rng = thread_rng();
@@ -960,6 +1324,92 @@ mod lattice_test {
         assert_eq!(ciphertext, ciphertext_again);
     }
 
+    #[test]
+    fn extract_bit_agrees_with_branching_implementation() {
+        let branching_extract_bit = |chunk: u64| -> u8 {
+            if chunk < (1 << 14) || (1 << 16) - chunk < (1 << 14) {
+                0
+            } else {
+                1
+            }
+        };
+
+        for chunk in 0..1 << 16 {
+            assert_eq!(branching_extract_bit(chunk), extract_bit(chunk), "{chunk}");
+        }
+    }
+
+    #[test]
+    fn kem_byte_encodings_round_trip() {
+        let (sk, pk) = kem::keygen(random());
+        let (_, ctxt) = kem::enc(pk, random());
+
+        let sk_bytes: [u8; kem::SECRET_KEY_SIZE_IN_BYTES] = sk.into();
+        assert_eq!(sk, SecretKey::from(sk_bytes));
+
+        let pk_bytes: [u8; kem::PUBLIC_KEY_SIZE_IN_BYTES] = pk.into();
+        assert_eq!(pk, PublicKey::try_from(pk_bytes).unwrap());
+
+        let ctxt_bytes: [u8; kem::CIPHERTEXT_SIZE_IN_BYTES] = ctxt.into();
+        assert_eq!(ctxt, Ciphertext::try_from(ctxt_bytes).unwrap());
+    }
+
+    #[test]
+    fn kem_byte_decodings_reject_non_canonical_elements() {
+        let (_, pk) = kem::keygen(random());
+        let (_, ctxt) = kem::enc(pk, random());
+        let non_canonical = u64::MAX.to_le_bytes();
+
+        let mut pk_bytes: [u8; kem::PUBLIC_KEY_SIZE_IN_BYTES] = pk.into();
+        pk_bytes[32..40].copy_from_slice(&non_canonical);
+        let pk_err = PublicKey::try_from(pk_bytes).unwrap_err();
+        assert_eq!(TryFromKemBytesError::NotCanonical(u64::MAX), pk_err);
+
+        let mut ctxt_bytes: [u8; kem::CIPHERTEXT_SIZE_IN_BYTES] = ctxt.into();
+        ctxt_bytes[..8].copy_from_slice(&non_canonical);
+        let ctxt_err = Ciphertext::try_from(ctxt_bytes).unwrap_err();
+        assert_eq!(TryFromKemBytesError::NotCanonical(u64::MAX), ctxt_err);
+    }
+
+    #[test]
+    fn implicit_rejection_decapsulation_agrees_with_encapsulation() {
+        let (sk, pk) = kem::keygen(random());
+        let (alice_key, ctxt) = kem::enc(pk, random());
+        assert_eq!(alice_key, kem::dec_implicit_rejection(sk, ctxt));
+    }
+
+    #[test]
+    fn implicit_rejection_decapsulation_of_invalid_ciphertext_gives_pseudorandom_key() {
+        let (sk, pk) = kem::keygen(random());
+        let (alice_key, ctxt) = kem::enc(pk, random());
+
+        let mut bfes: [BFieldElement; CIPHERTEXT_SIZE_IN_BFES] = ctxt.into();
+        bfes[0].increment();
+        let tampered_ctxt = Ciphertext::from(bfes);
+        let rejection_key = kem::dec_implicit_rejection(sk, tampered_ctxt);
+        assert_ne!(alice_key, rejection_key);
+        assert!(kem::dec(sk, tampered_ctxt).is_none());
+
+        // rejection is deterministic but depends on the ciphertext
+        assert_eq!(
+            rejection_key,
+            kem::dec_implicit_rejection(sk, tampered_ctxt)
+        );
+        bfes[1].increment();
+        let other_tampered_ctxt = Ciphertext::from(bfes);
+        assert_ne!(
+            rejection_key,
+            kem::dec_implicit_rejection(sk, other_tampered_ctxt)
+        );
+
+        // rejection depends on the secret key
+        let (other_sk, _) = kem::keygen(random());
+        assert_ne!(
+            rejection_key,
+            kem::dec_implicit_rejection(other_sk, tampered_ctxt)
+        );
+    }
+
     #[test]
     fn zero_test() {
         let zero_me = ModuleElement::<4>::zero();
@@ -1,5 +1,13 @@
 pub mod algebraic_hasher;
+pub mod beacon;
+pub mod commitment;
 pub mod merkle_tree;
 pub mod merkle_tree_maker;
 pub mod mmr;
+pub mod pow;
+pub mod secret_sharing;
+pub mod security_level;
 pub mod shared;
+pub mod transcript;
+pub mod wots;
+pub mod xmss;
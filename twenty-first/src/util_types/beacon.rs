@@ -0,0 +1,205 @@
+//! A randomness beacon combining contributions from many parties.
+//!
+//! Every party contributes a [`Digest`]. All contributions are absorbed into a [`Transcript`],
+//! from which the [prefix] of a [proof-of-work](crate::util_types::pow) puzzle is derived. The
+//! beacon's [`Output`] is only determined once the _smallest_ solution to that puzzle has been
+//! found. Hence, the last party to contribute cannot predict the output of candidate
+//! contributions without solving one puzzle per candidate, which bounds their ability to bias
+//! the output. Because the smallest solution is unique, whoever solves the puzzle has no
+//! choice to influence the output either.
+//!
+//! The delay imposed by the puzzle is a lower bound on _work_, not on sequential time: the
+//! search parallelizes. Verification requires the same amount of work as evaluation.
+
+use std::sync::atomic::AtomicBool;
+
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::math::b_field_element::BFieldElement;
+use crate::math::bfield_codec::BFieldCodec;
+use crate::math::digest::Digest;
+use crate::math::tip5::Tip5;
+use crate::math::x_field_element::XFieldElement;
+use crate::math::x_field_element::EXTENSION_DEGREE;
+use crate::util_types::pow;
+use crate::util_types::transcript::Transcript;
+
+const PROTOCOL: &str = "twenty-first-beacon-v1";
+
+/// The result of [evaluating](evaluate) the beacon.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, BFieldCodec)]
+pub struct Output {
+    /// The smallest solution to the proof-of-work puzzle.
+    pub nonce: u64,
+
+    /// The beacon's randomness. Use [`sample_scalars`](Self::sample_scalars) or
+    /// [`sample_bfield_elements`](Self::sample_bfield_elements) to derive field elements.
+    pub randomness: Digest,
+}
+
+/// The prefix of the proof-of-work puzzle for the given contributions. The order of the
+/// contributions matters.
+pub fn prefix(contributions: &[Digest]) -> Digest {
+    transcript(contributions).sample_digest("prefix")
+}
+
+/// Evaluate the beacon by solving the proof-of-work puzzle of the given difficulty, measured in
+/// the expected base-2 logarithm of the number of hash evaluations. See [`pow::target`].
+///
+/// The evaluation can be aborted by setting `cancel` to `true`, in which case `None` is
+/// returned.
+///
+/// # Panics
+///
+/// - Panics if the difficulty is not a valid [target](pow::target).
+pub fn evaluate(contributions: &[Digest], difficulty: u32, cancel: &AtomicBool) -> Option<Output> {
+    let mut transcript = transcript(contributions);
+    let prefix = transcript.sample_digest("prefix");
+    let nonce = pow::solve_smallest(prefix, pow::target(difficulty), cancel)?;
+
+    transcript.absorb("nonce", &nonce);
+    let randomness = transcript.sample_digest("randomness");
+    Some(Output { nonce, randomness })
+}
+
+/// Check that the output is the beacon's result for the given contributions and difficulty.
+/// This is as expensive as [evaluating](evaluate) the beacon.
+///
+/// # Panics
+///
+/// - Panics if the difficulty is not a valid [target](pow::target).
+pub fn verify(contributions: &[Digest], difficulty: u32, output: Output) -> bool {
+    let mut transcript = transcript(contributions);
+    let prefix = transcript.sample_digest("prefix");
+    if !pow::verify_smallest(prefix, pow::target(difficulty), output.nonce) {
+        return false;
+    }
+
+    transcript.absorb("nonce", &output.nonce);
+    transcript.sample_digest("randomness") == output.randomness
+}
+
+fn transcript(contributions: &[Digest]) -> Transcript<Tip5> {
+    let mut transcript = Transcript::new(PROTOCOL);
+    transcript.absorb("contributions", &contributions.to_vec());
+    transcript
+}
+
+impl Output {
+    /// Derive `num_scalars` [`XFieldElement`]s from the beacon's randomness. The label
+    /// separates the outputs for different purposes.
+    pub fn sample_scalars(&self, label: &'static str, num_scalars: usize) -> Vec<XFieldElement> {
+        self.output_transcript().sample_scalars(label, num_scalars)
+    }
+
+    /// Derive `num_elements` [`BFieldElement`]s from the beacon's randomness. The label
+    /// separates the outputs for different purposes.
+    pub fn sample_bfield_elements(
+        &self,
+        label: &'static str,
+        num_elements: usize,
+    ) -> Vec<BFieldElement> {
+        let num_scalars = num_elements.div_ceil(EXTENSION_DEGREE);
+        self.sample_scalars(label, num_scalars)
+            .into_iter()
+            .flat_map(|scalar| scalar.coefficients)
+            .take(num_elements)
+            .collect()
+    }
+
+    fn output_transcript(&self) -> Transcript<Tip5> {
+        let mut transcript = Transcript::new(PROTOCOL);
+        transcript.absorb_digest("randomness", self.randomness);
+        transcript
+    }
+}
+
+#[cfg(test)]
+mod beacon_tests {
+    use itertools::Itertools;
+    use proptest::collection::vec;
+    use proptest::prelude::*;
+    use proptest_arbitrary_interop::arb;
+    use test_strategy::proptest;
+
+    use super::*;
+
+    const DIFFICULTY: u32 = 6;
+
+    #[proptest(cases = 10)]
+    fn evaluation_verifies(#[strategy(vec(arb(), 0..5))] contributions: Vec<Digest>) {
+        let output = evaluate(&contributions, DIFFICULTY, &AtomicBool::new(false)).unwrap();
+        prop_assert!(verify(&contributions, DIFFICULTY, output));
+    }
+
+    #[proptest(cases = 10)]
+    fn evaluation_is_deterministic(#[strategy(vec(arb(), 0..5))] contributions: Vec<Digest>) {
+        let output = evaluate(&contributions, DIFFICULTY, &AtomicBool::new(false));
+        let output_again = evaluate(&contributions, DIFFICULTY, &AtomicBool::new(false));
+        prop_assert_eq!(output, output_again);
+    }
+
+    #[proptest(cases = 10)]
+    fn output_for_different_contributions_does_not_verify(
+        #[strategy(vec(arb(), 1..5))] contributions: Vec<Digest>,
+        #[strategy(arb())] additional_contribution: Digest,
+    ) {
+        let output = evaluate(&contributions, DIFFICULTY, &AtomicBool::new(false)).unwrap();
+
+        let mut extended_contributions = contributions.clone();
+        extended_contributions.push(additional_contribution);
+        prop_assert!(!verify(&extended_contributions, DIFFICULTY, output));
+
+        let reordered_contributions = contributions.iter().rev().copied().collect_vec();
+        prop_assume!(contributions != reordered_contributions);
+        prop_assert!(!verify(&reordered_contributions, DIFFICULTY, output));
+    }
+
+    #[proptest(cases = 10)]
+    fn tampered_output_does_not_verify(
+        #[strategy(vec(arb(), 0..5))] contributions: Vec<Digest>,
+        #[strategy(arb())] other_randomness: Digest,
+    ) {
+        let output = evaluate(&contributions, DIFFICULTY, &AtomicBool::new(false)).unwrap();
+        prop_assume!(output.randomness != other_randomness);
+
+        let tampered_randomness = Output {
+            randomness: other_randomness,
+            ..output
+        };
+        prop_assert!(!verify(&contributions, DIFFICULTY, tampered_randomness));
+
+        let prefix = prefix(&contributions);
+        let target = pow::target(DIFFICULTY);
+        let larger_nonce = (output.nonce + 1..)
+            .find(|&nonce| pow::verify(prefix, target, nonce))
+            .unwrap();
+        let tampered_nonce = Output {
+            nonce: larger_nonce,
+            ..output
+        };
+        prop_assert!(!verify(&contributions, DIFFICULTY, tampered_nonce));
+    }
+
+    #[test]
+    fn cancelled_evaluation_returns_nothing() {
+        let cancel = AtomicBool::new(true);
+        assert_eq!(None, evaluate(&[Digest::default()], 200, &cancel));
+    }
+
+    #[test]
+    fn labels_separate_derived_randomness() {
+        let output = evaluate(&[Digest::default()], DIFFICULTY, &AtomicBool::new(false)).unwrap();
+        assert_eq!(3, output.sample_scalars("alpha", 3).len());
+        assert_eq!(5, output.sample_bfield_elements("alpha", 5).len());
+        assert_eq!(
+            output.sample_bfield_elements("alpha", 2),
+            output.sample_bfield_elements("alpha", 2)
+        );
+        assert_ne!(
+            output.sample_bfield_elements("alpha", 2),
+            output.sample_bfield_elements("beta", 2)
+        );
+    }
+}
@@ -0,0 +1,127 @@
+//! Hiding and binding commitments to sequences of [`BFieldElement`]s, based on [`Tip5`].
+//!
+//! A commitment is the hash of a domain separator, a random blinding, and the committed value.
+//! The blinding makes the commitment hiding even if the value has little entropy; it must be
+//! sampled uniformly at random and kept secret until the commitment is opened. The domain
+//! separator ensures that commitments can never coincide with other uses of [`Tip5`].
+
+use rand::Rng;
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::math::b_field_element::BFieldElement;
+use crate::math::bfield_codec::BFieldCodec;
+use crate::math::digest::Digest;
+use crate::math::tip5::Tip5;
+use crate::util_types::algebraic_hasher::AlgebraicHasher;
+
+/// Prepended to all committed values, encoded one element per byte.
+const DOMAIN_SEPARATOR: &[u8] = b"twenty-first-commitment-v1";
+
+/// Everything needed to open a commitment.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, BFieldCodec)]
+pub struct Opening {
+    pub value: Vec<BFieldElement>,
+    pub blinding: Digest,
+}
+
+/// Commit to the value using the given blinding, which must be sampled uniformly at random.
+/// Use [`Opening::new`] to sample the blinding.
+pub fn commit(value: &[BFieldElement], blinding: Digest) -> Digest {
+    let domain_separator = DOMAIN_SEPARATOR
+        .iter()
+        .map(|&byte| BFieldElement::new(byte.into()));
+    let input = domain_separator
+        .chain(blinding.values())
+        .chain(value.iter().copied())
+        .collect::<Vec<_>>();
+    Tip5::hash_varlen(&input)
+}
+
+/// Check that the commitment opens to the given value with the given blinding.
+pub fn verify(commitment: Digest, value: &[BFieldElement], blinding: Digest) -> bool {
+    commit(value, blinding) == commitment
+}
+
+impl Opening {
+    /// An opening for the value with a freshly sampled blinding.
+    pub fn new<R: Rng + ?Sized>(value: Vec<BFieldElement>, rng: &mut R) -> Self {
+        let blinding = rng.gen();
+        Self { value, blinding }
+    }
+
+    /// The commitment that this opening opens.
+    pub fn commitment(&self) -> Digest {
+        commit(&self.value, self.blinding)
+    }
+
+    /// Check that this opening opens the given commitment.
+    pub fn verify(&self, commitment: Digest) -> bool {
+        verify(commitment, &self.value, self.blinding)
+    }
+}
+
+#[cfg(test)]
+mod commitment_tests {
+    use proptest::prelude::*;
+    use proptest_arbitrary_interop::arb;
+    use test_strategy::proptest;
+
+    use super::*;
+
+    #[proptest]
+    fn commitment_opens_to_committed_value(
+        #[strategy(arb())] value: Vec<BFieldElement>,
+        #[strategy(arb())] blinding: Digest,
+    ) {
+        let commitment = commit(&value, blinding);
+        prop_assert!(verify(commitment, &value, blinding));
+
+        let opening = Opening { value, blinding };
+        prop_assert_eq!(commitment, opening.commitment());
+        prop_assert!(opening.verify(commitment));
+    }
+
+    #[proptest]
+    fn commitment_does_not_open_to_different_value(
+        #[strategy(arb())] value: Vec<BFieldElement>,
+        #[strategy(arb())]
+        #[filter(#value != #other_value)]
+        other_value: Vec<BFieldElement>,
+        #[strategy(arb())] blinding: Digest,
+    ) {
+        let commitment = commit(&value, blinding);
+        prop_assert!(!verify(commitment, &other_value, blinding));
+    }
+
+    #[proptest]
+    fn commitment_does_not_open_with_different_blinding(
+        #[strategy(arb())] value: Vec<BFieldElement>,
+        #[strategy(arb())] blinding: Digest,
+        #[strategy(arb())]
+        #[filter(#blinding != #other_blinding)]
+        other_blinding: Digest,
+    ) {
+        let commitment = commit(&value, blinding);
+        prop_assert!(!verify(commitment, &value, other_blinding));
+    }
+
+    #[proptest]
+    fn commitment_differs_from_plain_hash(
+        #[strategy(arb())] value: Vec<BFieldElement>,
+        #[strategy(arb())] blinding: Digest,
+    ) {
+        let commitment = commit(&value, blinding);
+        let blinded_value = [blinding.values().to_vec(), value].concat();
+        prop_assert_ne!(Tip5::hash_varlen(&blinded_value), commitment);
+    }
+
+    #[test]
+    fn fresh_openings_of_same_value_give_different_commitments() {
+        let value = vec![BFieldElement::new(42)];
+        let rng = &mut rand::thread_rng();
+        let opening = Opening::new(value.clone(), rng);
+        let other_opening = Opening::new(value, rng);
+        assert_ne!(opening.commitment(), other_opening.commitment());
+    }
+}
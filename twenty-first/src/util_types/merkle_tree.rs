@@ -25,14 +25,14 @@ lazy_static! {
 
 /// Enforces that all compilation targets have a consistent [`MAX_TREE_HEIGHT`].
 /// In particular, if `usize` has more than 32 bits, the maximum height of a
-/// Merkle tree is limited as if only 32 bits were available. If `usize` has
-/// less than 32 bits, compilation will fail.
+/// Merkle tree is limited as if only 32 bits were available. Node indices of
+/// trees of maximal height still fit into a 32-bit `usize`.
 ///
 /// Using a type other than `usize` could enable a higher maximum height, but
 /// would require a different storage mechanism for the Merkle tree's nodes:
 /// indexing into a `Vec<_>` can only be done with `usize`.
-const MAX_NUM_NODES: usize = 1 << 32;
-const MAX_NUM_LEAFS: usize = MAX_NUM_NODES / 2;
+const MAX_NUM_NODES: u64 = 1 << 32;
+const MAX_NUM_LEAFS: u64 = MAX_NUM_NODES / 2;
 
 /// The maximum height of a Merkle tree.
 pub const MAX_TREE_HEIGHT: usize = MAX_NUM_LEAFS.ilog2() as usize;
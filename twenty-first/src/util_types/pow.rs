@@ -0,0 +1,223 @@
+//! Proof-of-work over [`Tip5`].
+//!
+//! A nonce is a valid proof of work for some prefix and some target if the digest of
+//! `prefix || nonce` is less than the target. Digests are compared according to [`Digest`]'s
+//! implementation of [`Ord`], _i.e._, the last element is the most significant one.
+
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
+
+use rayon::prelude::*;
+
+use crate::math::b_field_element::BFieldElement;
+use crate::math::bfield_codec::BFieldCodec;
+use crate::math::digest::Digest;
+use crate::math::tip5::Tip5;
+use crate::util_types::algebraic_hasher::AlgebraicHasher;
+
+/// The number of bits in a [`Digest`], if every element were a `u64`.
+const NUM_DIGEST_BITS: u32 = Digest::LEN as u32 * u64::BITS;
+
+/// The number of nonces per thread that [`solve_smallest`] checks in one batch.
+const NUM_NONCES_PER_THREAD_AND_BATCH: u64 = 1 << 10;
+
+/// The target that a digest with `num_leading_zeros` leading zero bits is guaranteed to meet.
+/// Finding a nonce for this target requires approximately `2^num_leading_zeros` hash
+/// evaluations.
+///
+/// # Panics
+///
+/// - Panics if `num_leading_zeros` is 0 or not smaller than the number of bits in a digest.
+pub fn target(num_leading_zeros: u32) -> Digest {
+    assert!(
+        (1..NUM_DIGEST_BITS).contains(&num_leading_zeros),
+        "number of leading zeros must be in range 1..{NUM_DIGEST_BITS}"
+    );
+
+    let log2_target = NUM_DIGEST_BITS - num_leading_zeros;
+    let element_index = (log2_target / u64::BITS) as usize;
+    let mut target = Digest::default();
+    target.0[element_index] = BFieldElement::new(1 << (log2_target % u64::BITS));
+    target
+}
+
+/// The digest of `prefix || nonce`.
+pub fn hash(prefix: Digest, nonce: u64) -> Digest {
+    let input = [prefix.encode(), nonce.encode()].concat();
+    Tip5::hash_varlen(&input)
+}
+
+/// Check whether the nonce is a valid proof of work for the given prefix and target.
+pub fn verify(prefix: Digest, target: Digest, nonce: u64) -> bool {
+    hash(prefix, nonce) < target
+}
+
+/// Search for a nonce that is a valid proof of work for the given prefix and target, using all
+/// threads of rayon's global thread pool.
+///
+/// The search can be aborted by setting `cancel` to `true`, in which case `None` is returned. If
+/// more than one valid nonce exists, it is unspecified which one is returned.
+pub fn solve(prefix: Digest, target: Digest, cancel: &AtomicBool) -> Option<u64> {
+    (0..u64::MAX)
+        .into_par_iter()
+        .find_map_any(|nonce| {
+            if cancel.load(Ordering::Relaxed) {
+                Some(None)
+            } else if verify(prefix, target, nonce) {
+                Some(Some(nonce))
+            } else {
+                None
+            }
+        })
+        .flatten()
+}
+
+/// Like [`solve`], but returns the smallest valid nonce. The search still uses all threads of
+/// rayon's global thread pool, but generally takes slightly longer than [`solve`].
+///
+/// Since the smallest valid nonce is unique, this is useful if the solver must not be able to
+/// choose between different solutions.
+pub fn solve_smallest(prefix: Digest, target: Digest, cancel: &AtomicBool) -> Option<u64> {
+    // Searching all nonces at once would split them into one huge range per thread, leaving
+    // only the thread responsible for the smallest nonces doing useful work. Instead, search
+    // consecutive batches that are small enough for all threads to share.
+    let batch_size = rayon::current_num_threads() as u64 * NUM_NONCES_PER_THREAD_AND_BATCH;
+    solve_smallest_in_batches(prefix, target, cancel, batch_size)
+}
+
+fn solve_smallest_in_batches(
+    prefix: Digest,
+    target: Digest,
+    cancel: &AtomicBool,
+    batch_size: u64,
+) -> Option<u64> {
+    let mut batch_start = 0;
+    while batch_start < u64::MAX {
+        if cancel.load(Ordering::Relaxed) {
+            return None;
+        }
+        let batch_end = batch_start.saturating_add(batch_size);
+        let solution = (batch_start..batch_end)
+            .into_par_iter()
+            .find_first(|&nonce| verify(prefix, target, nonce));
+        if solution.is_some() {
+            return solution;
+        }
+        batch_start = batch_end;
+    }
+
+    None
+}
+
+/// Check whether the nonce is the smallest valid proof of work for the given prefix and target.
+/// This requires checking all smaller nonces, which is as expensive as [`solve_smallest`].
+pub fn verify_smallest(prefix: Digest, target: Digest, nonce: u64) -> bool {
+    verify(prefix, target, nonce)
+        && (0..nonce)
+            .into_par_iter()
+            .all(|smaller_nonce| !verify(prefix, target, smaller_nonce))
+}
+
+#[cfg(test)]
+mod pow_tests {
+    use std::sync::Arc;
+    use std::thread;
+    use std::time::Duration;
+
+    use proptest::prelude::*;
+    use proptest_arbitrary_interop::arb;
+    use test_strategy::proptest;
+
+    use super::*;
+
+    #[test]
+    fn target_for_few_leading_zeros_is_in_most_significant_element() {
+        let easy_target = target(1);
+        assert_eq!(BFieldElement::new(1 << 63), easy_target.0[Digest::LEN - 1]);
+        assert!(easy_target.0[..Digest::LEN - 1]
+            .iter()
+            .all(|&e| e.value() == 0));
+    }
+
+    #[test]
+    fn target_for_many_leading_zeros_is_in_lower_element() {
+        let hard_target = target(64 + 3);
+        assert_eq!(BFieldElement::new(1 << 61), hard_target.0[Digest::LEN - 2]);
+        assert_eq!(0, hard_target.0[Digest::LEN - 1].value());
+    }
+
+    #[test]
+    fn targets_decrease_with_difficulty() {
+        for num_leading_zeros in 1..NUM_DIGEST_BITS - 1 {
+            assert!(target(num_leading_zeros + 1) < target(num_leading_zeros));
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "number of leading zeros")]
+    fn target_without_leading_zeros_is_invalid() {
+        target(0);
+    }
+
+    #[proptest(cases = 10)]
+    fn solution_verifies(#[strategy(arb())] prefix: Digest) {
+        let nonce = solve(prefix, target(6), &AtomicBool::new(false)).unwrap();
+        prop_assert!(verify(prefix, target(6), nonce));
+    }
+
+    #[proptest(cases = 10)]
+    fn solution_is_bound_to_prefix(
+        #[strategy(arb())] prefix: Digest,
+        #[strategy(arb())] other_prefix: Digest,
+    ) {
+        prop_assume!(prefix != other_prefix);
+        let nonce = solve(other_prefix, target(6), &AtomicBool::new(false)).unwrap();
+        prop_assert!(verify(other_prefix, target(6), nonce));
+        prop_assert_ne!(hash(other_prefix, nonce), hash(prefix, nonce));
+    }
+
+    #[proptest(cases = 10)]
+    fn smallest_solution_is_unique(#[strategy(arb())] prefix: Digest) {
+        let nonce = solve_smallest(prefix, target(6), &AtomicBool::new(false)).unwrap();
+        prop_assert!(verify_smallest(prefix, target(6), nonce));
+        prop_assert!((0..nonce).all(|smaller_nonce| !verify(prefix, target(6), smaller_nonce)));
+
+        let larger_nonce = (nonce + 1..)
+            .find(|&n| verify(prefix, target(6), n))
+            .unwrap();
+        prop_assert!(!verify_smallest(prefix, target(6), larger_nonce));
+    }
+
+    #[proptest(cases = 20)]
+    fn smallest_solution_agrees_with_sequential_search(
+        #[strategy(arb())] prefix: Digest,
+        #[strategy(1_u64..100)] batch_size: u64,
+    ) {
+        let target = target(8);
+        let cancel = AtomicBool::new(false);
+        let solution = solve_smallest_in_batches(prefix, target, &cancel, batch_size);
+        let sequential_solution = (0..u64::MAX).find(|&nonce| verify(prefix, target, nonce));
+        prop_assert_eq!(sequential_solution, solution);
+        prop_assert_eq!(solution, solve_smallest(prefix, target, &cancel));
+    }
+
+    #[test]
+    fn cancelled_search_returns_nothing() {
+        let cancel = AtomicBool::new(true);
+        assert_eq!(None, solve(rand::random(), target(200), &cancel));
+        assert_eq!(None, solve_smallest(rand::random(), target(200), &cancel));
+    }
+
+    #[test]
+    fn search_can_be_cancelled_from_another_thread() {
+        let cancel = Arc::new(AtomicBool::new(false));
+        let cancel_clone = Arc::clone(&cancel);
+        let canceller = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(50));
+            cancel_clone.store(true, Ordering::Relaxed);
+        });
+
+        assert_eq!(None, solve(rand::random(), target(200), &cancel));
+        canceller.join().unwrap();
+    }
+}
@@ -0,0 +1,505 @@
+//! [Shamir secret sharing][shamir] over the [`BFieldElement`]s.
+//!
+//! A secret is [split] into shares such that any `threshold` many of them suffice to
+//! [reconstruct] the secret, while fewer reveal nothing about it. The shares are
+//! evaluations of a random polynomial of degree `threshold - 1` whose constant term is the
+//! secret.
+//!
+//! In the [committed](split_committed) variant, the dealer additionally publishes a
+//! [`Commitment`]: the root of a Merkle tree whose leafs are blinded hashes of the shares, the
+//! threshold, and the number of shares. Every shareholder can check their share against the
+//! commitment, and reconstruction rejects shares that were not committed to.
+//!
+//! Note that this does _not_ make the sharing verifiable. Since hash commitments are not
+//! homomorphic, a shareholder cannot check that the committed shares lie on one polynomial of
+//! degree less than `threshold`. Only [reconstruction](Commitment::reconstruct) from more than
+//! `threshold` shares detects if the supplied shares are inconsistent.
+//!
+//! [shamir]: https://dl.acm.org/doi/10.1145/359168.359176
+
+use std::marker::PhantomData;
+
+use arbitrary::Arbitrary;
+use itertools::Itertools;
+use num_traits::ConstZero;
+use num_traits::Zero;
+use rand::Rng;
+use serde::Deserialize;
+use serde::Serialize;
+use thiserror::Error;
+
+use crate::math::b_field_element::BFieldElement;
+use crate::math::bfield_codec::BFieldCodec;
+use crate::math::digest::Digest;
+use crate::math::polynomial::Polynomial;
+use crate::math::tip5::Tip5;
+use crate::util_types::commitment;
+use crate::util_types::merkle_tree::CpuParallel;
+use crate::util_types::merkle_tree::MerkleTree;
+use crate::util_types::merkle_tree::MerkleTreeError;
+use crate::util_types::merkle_tree::MerkleTreeInclusionProof;
+use crate::util_types::merkle_tree::MAX_TREE_HEIGHT;
+
+type Result<T> = std::result::Result<T, SecretSharingError>;
+
+/// One share of a secret: the evaluation of the sharing polynomial in `index`.
+#[derive(
+    Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, BFieldCodec, Arbitrary,
+)]
+pub struct Share {
+    pub index: BFieldElement,
+    pub value: BFieldElement,
+}
+
+/// The dealer's binding commitment to all shares of a [committed sharing](split_committed).
+///
+/// The `root` commits to the `threshold` and the `num_shares`, too: shares do not
+/// [verify](Self::verify) against a commitment with either of them altered.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Commitment {
+    pub root: Digest,
+    pub threshold: usize,
+    pub num_shares: usize,
+}
+
+/// A [`Share`] together with everything needed to check it against a [`Commitment`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, BFieldCodec)]
+pub struct CommittedShare {
+    pub share: Share,
+    pub blinding: Digest,
+
+    /// The authentication path of the blinded share's hash, bottom-up.
+    pub authentication_path: Vec<Digest>,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Error)]
+#[non_exhaustive]
+pub enum SecretSharingError {
+    #[error("The threshold must be at least 1.")]
+    ZeroThreshold,
+
+    #[error("The threshold ({threshold}) must not exceed the number of shares ({num_shares}).")]
+    ThresholdTooLarge { threshold: usize, num_shares: usize },
+
+    #[error("The number of shares must not exceed 2^{MAX_TREE_HEIGHT}.")]
+    TooManyShares,
+
+    #[error("Reconstruction requires at least {threshold} shares, but got {num_shares}.")]
+    NotEnoughShares { threshold: usize, num_shares: usize },
+
+    #[error("Share index must not be 0.")]
+    ZeroIndex,
+
+    #[error("Share index {0} occurs more than once.")]
+    DuplicateIndex(BFieldElement),
+
+    #[error("Share with index {0} does not match the commitment.")]
+    InvalidShare(BFieldElement),
+
+    #[error("Shares do not lie on a polynomial of degree less than the threshold.")]
+    InconsistentShares,
+
+    #[error("Merkle tree error: {0}")]
+    MerkleTree(#[from] MerkleTreeError),
+}
+
+/// Split the secret into `num_shares` shares, any `threshold` many of which suffice to
+/// [reconstruct] the secret. The shares have indices `1..=num_shares`.
+pub fn split<R: Rng + ?Sized>(
+    secret: BFieldElement,
+    threshold: usize,
+    num_shares: usize,
+    rng: &mut R,
+) -> Result<Vec<Share>> {
+    if threshold == 0 {
+        return Err(SecretSharingError::ZeroThreshold);
+    }
+    if threshold > num_shares {
+        return Err(SecretSharingError::ThresholdTooLarge {
+            threshold,
+            num_shares,
+        });
+    }
+
+    let random_coefficients = (1..threshold).map(|_| rng.gen::<BFieldElement>());
+    let coefficients = [secret].into_iter().chain(random_coefficients).collect();
+    let polynomial = Polynomial::new(coefficients);
+
+    let indices = (1..=num_shares as u64)
+        .map(BFieldElement::new)
+        .collect_vec();
+    let values = polynomial.batch_evaluate(&indices);
+    let shares = indices
+        .into_iter()
+        .zip(values)
+        .map(|(index, value)| Share { index, value })
+        .collect();
+    Ok(shares)
+}
+
+/// Reconstruct the secret from the given shares.
+///
+/// If fewer shares than the threshold used for [splitting](split) are supplied, the result is
+/// unrelated to the secret. Supplying more shares than the threshold is fine.
+pub fn reconstruct(shares: &[Share]) -> Result<BFieldElement> {
+    Ok(interpolate(shares)?.evaluate(BFieldElement::ZERO))
+}
+
+/// The polynomial of least degree passing through all the shares.
+fn interpolate(shares: &[Share]) -> Result<Polynomial<BFieldElement>> {
+    if shares.is_empty() {
+        return Err(SecretSharingError::NotEnoughShares {
+            threshold: 1,
+            num_shares: 0,
+        });
+    }
+    if shares.iter().any(|share| share.index.is_zero()) {
+        return Err(SecretSharingError::ZeroIndex);
+    }
+    if let Some(index) = shares.iter().map(|share| share.index).duplicates().next() {
+        return Err(SecretSharingError::DuplicateIndex(index));
+    }
+
+    let indices = shares.iter().map(|share| share.index).collect_vec();
+    let values = shares.iter().map(|share| share.value).collect_vec();
+    Ok(Polynomial::interpolate(&indices, &values))
+}
+
+/// Split the secret like [`split`] and commit to the resulting shares. The commitment is
+/// published, and every shareholder receives their [`CommittedShare`].
+pub fn split_committed<R: Rng + ?Sized>(
+    secret: BFieldElement,
+    threshold: usize,
+    num_shares: usize,
+    rng: &mut R,
+) -> Result<(Commitment, Vec<CommittedShare>)> {
+    tree_height(num_shares)?;
+    let shares = split(secret, threshold, num_shares, rng)?;
+    commit_to_shares(shares, threshold, rng)
+}
+
+/// Commit to the given shares, claiming that any `threshold` many of them suffice for
+/// reconstruction. Does not check the claim.
+fn commit_to_shares<R: Rng + ?Sized>(
+    shares: Vec<Share>,
+    threshold: usize,
+    rng: &mut R,
+) -> Result<(Commitment, Vec<CommittedShare>)> {
+    let num_shares = shares.len();
+    let tree_height = tree_height(num_shares)?;
+    let blindings = (0..num_shares).map(|_| rng.gen::<Digest>()).collect_vec();
+    let commitment_without_root = Commitment {
+        root: Digest::ALL_ZERO,
+        threshold,
+        num_shares,
+    };
+
+    let num_padding_leafs = (1 << tree_height) - num_shares;
+    let leafs = shares
+        .iter()
+        .zip(&blindings)
+        .map(|(&share, &blinding)| commitment_without_root.leaf(share, blinding))
+        .chain(vec![Digest::ALL_ZERO; num_padding_leafs])
+        .collect_vec();
+    let tree = MerkleTree::<Tip5>::new::<CpuParallel>(&leafs)?;
+
+    let commitment = Commitment {
+        root: tree.root(),
+        ..commitment_without_root
+    };
+    let committed_shares = shares
+        .into_iter()
+        .zip(blindings)
+        .enumerate()
+        .map(|(leaf_index, (share, blinding))| {
+            let authentication_path = tree.authentication_structure(&[leaf_index])?;
+            Ok(CommittedShare {
+                share,
+                blinding,
+                authentication_path,
+            })
+        })
+        .collect::<Result<_>>()?;
+
+    Ok((commitment, committed_shares))
+}
+
+impl Commitment {
+    /// Check that the share is one of the shares committed to.
+    pub fn verify(&self, share: &CommittedShare) -> bool {
+        let Ok(tree_height) = tree_height(self.num_shares) else {
+            return false;
+        };
+        let index = share.share.index.value();
+        if index == 0 || index > self.num_shares as u64 {
+            return false;
+        }
+        if share.authentication_path.len() != tree_height {
+            return false;
+        }
+
+        let leaf_index = (index - 1) as usize;
+        let inclusion_proof = MerkleTreeInclusionProof::<Tip5> {
+            tree_height,
+            indexed_leafs: vec![(leaf_index, self.leaf(share.share, share.blinding))],
+            authentication_structure: share.authentication_path.clone(),
+            _hasher: PhantomData,
+        };
+        inclusion_proof.verify(self.root)
+    }
+
+    /// Reconstruct the secret from at least `threshold` many shares, all of which must
+    /// [verify](Self::verify). If more than `threshold` shares are supplied, they must
+    /// additionally be consistent with one another.
+    pub fn reconstruct(&self, shares: &[CommittedShare]) -> Result<BFieldElement> {
+        if shares.len() < self.threshold {
+            return Err(SecretSharingError::NotEnoughShares {
+                threshold: self.threshold,
+                num_shares: shares.len(),
+            });
+        }
+        if let Some(invalid_share) = shares.iter().find(|share| !self.verify(share)) {
+            return Err(SecretSharingError::InvalidShare(invalid_share.share.index));
+        }
+
+        let shares = shares.iter().map(|share| share.share).collect_vec();
+        let polynomial = interpolate(&shares)?;
+        if polynomial.degree() >= self.threshold as isize {
+            return Err(SecretSharingError::InconsistentShares);
+        }
+
+        Ok(polynomial.evaluate(BFieldElement::ZERO))
+    }
+
+    /// The [blinded commitment](commitment::commit) to a share, the threshold, and the number
+    /// of shares.
+    fn leaf(&self, share: Share, blinding: Digest) -> Digest {
+        let parameters = [self.threshold, self.num_shares].map(|p| BFieldElement::new(p as u64));
+        let preimage = [share.encode(), parameters.to_vec()].concat();
+        commitment::commit(&preimage, blinding)
+    }
+}
+
+/// The height of the Merkle tree committing to the given number of shares.
+fn tree_height(num_shares: usize) -> Result<usize> {
+    let Some(num_leafs) = num_shares.checked_next_power_of_two() else {
+        return Err(SecretSharingError::TooManyShares);
+    };
+    let tree_height = num_leafs.ilog2() as usize;
+    if tree_height > MAX_TREE_HEIGHT {
+        return Err(SecretSharingError::TooManyShares);
+    }
+    Ok(tree_height)
+}
+
+#[cfg(test)]
+mod secret_sharing_tests {
+    use proptest::prelude::*;
+    use proptest_arbitrary_interop::arb;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use test_strategy::proptest;
+
+    use super::*;
+
+    #[proptest(cases = 20)]
+    fn any_threshold_many_shares_reconstruct_secret(
+        #[strategy(arb())] secret: BFieldElement,
+        #[strategy(1_usize..20)] threshold: usize,
+        #[strategy(#threshold..30)] num_shares: usize,
+        #[strategy(Just((0..#num_shares).collect_vec()).prop_shuffle())] share_order: Vec<usize>,
+        seed: u64,
+    ) {
+        let rng = &mut StdRng::seed_from_u64(seed);
+        let shares = split(secret, threshold, num_shares, rng).unwrap();
+        prop_assert_eq!(num_shares, shares.len());
+
+        let chosen_shares = share_order[..threshold]
+            .iter()
+            .map(|&i| shares[i])
+            .collect_vec();
+        prop_assert_eq!(secret, reconstruct(&chosen_shares).unwrap());
+        prop_assert_eq!(secret, reconstruct(&shares).unwrap());
+    }
+
+    #[proptest(cases = 20)]
+    fn fewer_than_threshold_many_shares_do_not_determine_secret(
+        #[strategy(arb())] secret: BFieldElement,
+        #[strategy(2_usize..20)] threshold: usize,
+        seed: u64,
+    ) {
+        let rng = &mut StdRng::seed_from_u64(seed);
+        let shares = split(secret, threshold, threshold, rng).unwrap();
+        prop_assert_ne!(secret, reconstruct(&shares[1..]).unwrap());
+    }
+
+    #[test]
+    fn threshold_one_shares_are_the_secret() {
+        let secret = BFieldElement::new(42);
+        let shares = split(secret, 1, 5, &mut rand::thread_rng()).unwrap();
+        assert!(shares.iter().all(|share| share.value == secret));
+    }
+
+    #[test]
+    fn invalid_sharing_parameters_are_rejected() {
+        let secret = BFieldElement::new(42);
+        let rng = &mut rand::thread_rng();
+        assert_eq!(
+            SecretSharingError::ZeroThreshold,
+            split(secret, 0, 3, rng).unwrap_err()
+        );
+
+        let expected = SecretSharingError::ThresholdTooLarge {
+            threshold: 4,
+            num_shares: 3,
+        };
+        assert_eq!(expected, split(secret, 4, 3, rng).unwrap_err());
+    }
+
+    #[proptest]
+    fn reconstruction_rejects_malformed_shares(#[strategy(arb())] share: Share) {
+        let duplicate_err = reconstruct(&[share, share]).unwrap_err();
+        if share.index.is_zero() {
+            prop_assert_eq!(SecretSharingError::ZeroIndex, duplicate_err);
+        } else {
+            prop_assert_eq!(
+                SecretSharingError::DuplicateIndex(share.index),
+                duplicate_err
+            );
+        }
+
+        let zero_index_share = Share {
+            index: BFieldElement::ZERO,
+            ..share
+        };
+        let zero_index_err = reconstruct(&[zero_index_share]).unwrap_err();
+        prop_assert_eq!(SecretSharingError::ZeroIndex, zero_index_err);
+    }
+
+    #[proptest(cases = 10)]
+    fn committed_shares_verify_and_reconstruct_secret(
+        #[strategy(arb())] secret: BFieldElement,
+        #[strategy(1_usize..10)] threshold: usize,
+        #[strategy(#threshold..20)] num_shares: usize,
+        seed: u64,
+    ) {
+        let rng = &mut StdRng::seed_from_u64(seed);
+        let (commitment, shares) = split_committed(secret, threshold, num_shares, rng).unwrap();
+        prop_assert!(shares.iter().all(|share| commitment.verify(share)));
+
+        let reconstructed = commitment.reconstruct(&shares[..threshold]).unwrap();
+        prop_assert_eq!(secret, reconstructed);
+        prop_assert_eq!(secret, commitment.reconstruct(&shares).unwrap());
+    }
+
+    #[proptest(cases = 10)]
+    fn tampered_committed_share_is_rejected(
+        #[strategy(arb())] secret: BFieldElement,
+        #[strategy(1_usize..10)] threshold: usize,
+        #[strategy(#threshold..20)] num_shares: usize,
+        #[strategy(0..#num_shares)] tampered_index: usize,
+        #[strategy(arb())] offset: BFieldElement,
+        seed: u64,
+    ) {
+        prop_assume!(!offset.is_zero());
+        let rng = &mut StdRng::seed_from_u64(seed);
+        let (commitment, mut shares) = split_committed(secret, threshold, num_shares, rng).unwrap();
+
+        shares[tampered_index].share.value += offset;
+        prop_assert!(!commitment.verify(&shares[tampered_index]));
+
+        let expected = SecretSharingError::InvalidShare(shares[tampered_index].share.index);
+        prop_assert_eq!(expected, commitment.reconstruct(&shares).unwrap_err());
+    }
+
+    #[test]
+    fn share_with_out_of_range_index_does_not_verify() {
+        let secret = BFieldElement::new(42);
+        let rng = &mut rand::thread_rng();
+        let (commitment, shares) = split_committed(secret, 2, 3, rng).unwrap();
+
+        for index in [0, 4] {
+            let mut share = shares[0].clone();
+            share.share.index = BFieldElement::new(index);
+            assert!(!commitment.verify(&share));
+        }
+    }
+
+    #[test]
+    fn commitment_to_absurdly_many_shares_does_not_verify() {
+        let secret = BFieldElement::new(42);
+        let rng = &mut rand::thread_rng();
+        let (commitment, shares) = split_committed(secret, 2, 3, rng).unwrap();
+
+        let malformed_commitment = Commitment {
+            num_shares: usize::MAX,
+            ..commitment
+        };
+        assert!(!malformed_commitment.verify(&shares[0]));
+        assert_eq!(
+            Err(SecretSharingError::TooManyShares),
+            tree_height(usize::MAX)
+        );
+    }
+
+    #[test]
+    fn reconstruction_from_too_few_committed_shares_fails() {
+        let secret = BFieldElement::new(42);
+        let rng = &mut rand::thread_rng();
+        let (commitment, shares) = split_committed(secret, 3, 5, rng).unwrap();
+
+        let expected = SecretSharingError::NotEnoughShares {
+            threshold: 3,
+            num_shares: 2,
+        };
+        assert_eq!(expected, commitment.reconstruct(&shares[..2]).unwrap_err());
+    }
+
+    #[test]
+    fn inconsistent_dealer_is_detected_given_enough_shares() {
+        let secret = BFieldElement::new(42);
+        let rng = &mut rand::thread_rng();
+
+        // A dishonest dealer commits to shares of a higher-degree polynomial.
+        let shares = split(secret, 3, 4, rng).unwrap();
+        let (dishonest_commitment, committed_shares) = commit_to_shares(shares, 2, rng).unwrap();
+        assert!(committed_shares
+            .iter()
+            .all(|share| dishonest_commitment.verify(share)));
+
+        let expected = SecretSharingError::InconsistentShares;
+        let err = dishonest_commitment
+            .reconstruct(&committed_shares)
+            .unwrap_err();
+        assert_eq!(expected, err);
+    }
+
+    #[proptest(cases = 10)]
+    fn commitment_binds_sharing_parameters(
+        #[strategy(arb())] secret: BFieldElement,
+        #[strategy(1_usize..10)] threshold: usize,
+        #[strategy(#threshold..20)] num_shares: usize,
+        #[strategy(1_usize..20)] other_threshold: usize,
+        #[strategy(#other_threshold.max(#num_shares)..20)] other_num_shares: usize,
+        seed: u64,
+    ) {
+        prop_assume!((threshold, num_shares) != (other_threshold, other_num_shares));
+        let rng = &mut StdRng::seed_from_u64(seed);
+        let (commitment, shares) = split_committed(secret, threshold, num_shares, rng).unwrap();
+
+        let altered_commitment = Commitment {
+            threshold: other_threshold,
+            num_shares: other_num_shares,
+            ..commitment
+        };
+        prop_assert!(shares.iter().all(|share| !altered_commitment.verify(share)));
+    }
+
+    #[test]
+    fn single_share_can_be_committed_to() {
+        let secret = BFieldElement::new(42);
+        let rng = &mut rand::thread_rng();
+        let (commitment, shares) = split_committed(secret, 1, 1, rng).unwrap();
+        assert_eq!(secret, commitment.reconstruct(&shares).unwrap());
+    }
+}
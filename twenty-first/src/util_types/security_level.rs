@@ -0,0 +1,301 @@
+//! Estimate the security level of a STARK from its FRI and AIR parameters.
+//!
+//! The estimate gives two numbers of bits:
+//! - The _conjectured_ security level is based on the [ethSTARK][ethstark] conjecture: every
+//!   FRI query contributes `log2(expansion_factor)` bits, every bit of [grinding] adds one bit,
+//!   and the size of the field caps the total.
+//! - The _proven_ security level only relies on soundness in the unique-decoding regime. Here,
+//!   every FRI query only contributes `-log2((1 + ρ) / 2)` bits, where `ρ` is the code rate
+//!   `1 / expansion_factor`. Additionally, the errors from batching the constraints, from the
+//!   out-of-domain sample, and from the FRI commit phase are accounted for.
+//!
+//! Both numbers ignore the collision resistance of the hash function; the resulting security
+//! level is the minimum of the estimate and the hash function's collision resistance.
+//!
+//! ```
+//! # use twenty_first::util_types::security_level::*;
+//! let parameters = Parameters {
+//!     fri_expansion_factor: 4,
+//!     num_queries: 80,
+//!     num_grinding_bits: 0,
+//!     log2_field_size: 191,
+//!     trace_length: 1 << 20,
+//!     constraint_degrees: vec![4; 100],
+//! };
+//! let security_level = estimate(&parameters).unwrap();
+//! assert_eq!(160, security_level.conjectured);
+//! assert_eq!(54, security_level.proven);
+//! ```
+//!
+//! [ethstark]: https://eprint.iacr.org/2021/582
+//! [grinding]: crate::util_types::transcript::Transcript::grind
+
+use serde::Deserialize;
+use serde::Serialize;
+use thiserror::Error;
+
+type Result<T> = std::result::Result<T, SecurityLevelError>;
+
+/// The parameters determining the security level of a STARK.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Parameters {
+    /// The ratio of the FRI domain's length to the trace length. Must be a power of 2 greater
+    /// than 1.
+    pub fri_expansion_factor: usize,
+
+    /// The number of FRI queries, also known as collinearity checks.
+    pub num_queries: usize,
+
+    /// The number of bits of [grinding](crate::util_types::transcript::Transcript::grind).
+    pub num_grinding_bits: u32,
+
+    /// The base-2 logarithm of the size of the field that challenges are sampled from, rounded
+    /// down. For the [`XFieldElement`](crate::math::x_field_element::XFieldElement)s, this is
+    /// 191.
+    pub log2_field_size: u32,
+
+    /// The length of the padded trace. Must be a power of 2.
+    pub trace_length: usize,
+
+    /// The degrees of the AIR constraints, relative to the degree of the trace polynomials.
+    pub constraint_degrees: Vec<usize>,
+}
+
+/// The estimated security level of a STARK, in bits.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct SecurityLevel {
+    /// The security level under the ethSTARK conjecture.
+    pub conjectured: u32,
+
+    /// The provable security level.
+    pub proven: u32,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Error)]
+#[non_exhaustive]
+pub enum SecurityLevelError {
+    #[error("The FRI expansion factor ({0}) must be a power of 2 greater than 1.")]
+    InvalidExpansionFactor(usize),
+
+    #[error("The trace length ({0}) must be a power of 2.")]
+    InvalidTraceLength(usize),
+
+    #[error("The FRI domain length overflows.")]
+    DomainTooLarge,
+}
+
+/// Estimate the conjectured and proven security level for the given parameters.
+pub fn estimate(parameters: &Parameters) -> Result<SecurityLevel> {
+    let expansion_factor = parameters.fri_expansion_factor;
+    if !expansion_factor.is_power_of_two() || expansion_factor < 2 {
+        return Err(SecurityLevelError::InvalidExpansionFactor(expansion_factor));
+    }
+    let trace_length = parameters.trace_length;
+    if !trace_length.is_power_of_two() {
+        return Err(SecurityLevelError::InvalidTraceLength(trace_length));
+    }
+    let Some(domain_length) = trace_length.checked_mul(expansion_factor) else {
+        return Err(SecurityLevelError::DomainTooLarge);
+    };
+
+    let security_level = SecurityLevel {
+        conjectured: to_bits(conjectured_security(parameters, domain_length)),
+        proven: to_bits(proven_security(parameters, domain_length)),
+    };
+    Ok(security_level)
+}
+
+fn conjectured_security(parameters: &Parameters, domain_length: usize) -> f64 {
+    let log2_expansion_factor = f64::from(parameters.fri_expansion_factor.ilog2());
+    let query_security = parameters.num_queries as f64 * log2_expansion_factor
+        + f64::from(parameters.num_grinding_bits);
+    let field_security = f64::from(parameters.log2_field_size) - f64::from(domain_length.ilog2());
+
+    query_security.min(field_security)
+}
+
+/// The negative base-2 logarithm of the sum of all soundness errors.
+fn proven_security(parameters: &Parameters, domain_length: usize) -> f64 {
+    let field_size = f64::from(parameters.log2_field_size).exp2();
+    let domain_length = domain_length as f64;
+    let trace_length = parameters.trace_length as f64;
+    let num_constraints = parameters.constraint_degrees.len() as f64;
+    let max_constraint_degree = parameters.constraint_degrees.iter().max().unwrap_or(&1);
+    let max_constraint_degree = *max_constraint_degree as f64;
+
+    // Combining the constraints with powers of a single random challenge.
+    let batching_error = num_constraints / field_size;
+
+    // The out-of-domain sample hitting a point where the composition polynomial agrees with
+    // the claimed low-degree polynomial by accident.
+    let deep_error = max_constraint_degree * trace_length / (field_size - domain_length).max(0.0);
+
+    // Every folding step of FRI errs with probability at most its domain length divided by the
+    // field size. The domain lengths halve, summing to less than twice the initial length.
+    let fri_commit_error = 2.0 * domain_length / field_size;
+
+    // A word that is further than the unique-decoding radius `(1 - ρ) / 2` from the code passes
+    // every query with probability at most `(1 + ρ) / 2`.
+    let rate = 1.0 / parameters.fri_expansion_factor as f64;
+    let log2_query_error = parameters.num_queries as f64 * ((1.0 + rate) / 2.0).log2()
+        - f64::from(parameters.num_grinding_bits);
+    let fri_query_error = log2_query_error.exp2();
+
+    let total_error = batching_error + deep_error + fri_commit_error + fri_query_error;
+    -total_error.log2()
+}
+
+fn to_bits(security: f64) -> u32 {
+    security.floor().clamp(0.0, f64::from(u32::MAX)) as u32
+}
+
+#[cfg(test)]
+mod security_level_tests {
+    use proptest::prelude::*;
+    use test_strategy::proptest;
+
+    use super::*;
+
+    /// Parameters resembling the defaults of a STARK over the extension field.
+    fn parameters() -> Parameters {
+        Parameters {
+            fri_expansion_factor: 4,
+            num_queries: 80,
+            num_grinding_bits: 0,
+            log2_field_size: 191,
+            trace_length: 1 << 20,
+            constraint_degrees: vec![4; 100],
+        }
+    }
+
+    #[test]
+    fn security_level_of_known_parameters() {
+        let security_level = estimate(&parameters()).unwrap();
+        assert_eq!(160, security_level.conjectured);
+
+        // 80 · log2(5/8) ≈ -54.25
+        assert_eq!(54, security_level.proven);
+    }
+
+    #[test]
+    fn conjectured_security_is_capped_by_field_size() {
+        let parameters = Parameters {
+            fri_expansion_factor: 8,
+            num_queries: 42,
+            num_grinding_bits: 16,
+            log2_field_size: 128,
+            trace_length: 1 << 20,
+            ..parameters()
+        };
+        let security_level = estimate(&parameters).unwrap();
+        assert_eq!(128 - 23, security_level.conjectured);
+    }
+
+    #[test]
+    fn small_field_limits_proven_security() {
+        let parameters = Parameters {
+            log2_field_size: 63,
+            num_queries: 200,
+            ..parameters()
+        };
+
+        // The FRI commit error of 2 · 2^22 / 2^63 and the out-of-domain sampling error of
+        // 4 · 2^20 / 2^63 dominate: -log2(2^-40 + 2^-41) ≈ 39.4
+        let security_level = estimate(&parameters).unwrap();
+        assert_eq!(39, security_level.proven);
+    }
+
+    #[test]
+    fn insufficient_field_size_gives_no_security() {
+        let parameters = Parameters {
+            log2_field_size: 16,
+            ..parameters()
+        };
+        let security_level = estimate(&parameters).unwrap();
+        assert_eq!(0, security_level.conjectured);
+        assert_eq!(0, security_level.proven);
+    }
+
+    /// Up to 9 grinding bits, the conjectured security of 160 bits stays below the field's cap
+    /// of 191 - 22 = 169 bits.
+    #[proptest]
+    fn grinding_adds_security(#[strategy(0_u32..10)] num_grinding_bits: u32) {
+        let baseline = estimate(&parameters()).unwrap();
+        let parameters = Parameters {
+            num_grinding_bits,
+            ..parameters()
+        };
+        let security_level = estimate(&parameters).unwrap();
+        prop_assert_eq!(
+            baseline.conjectured + num_grinding_bits,
+            security_level.conjectured
+        );
+        prop_assert_eq!(baseline.proven + num_grinding_bits, security_level.proven);
+    }
+
+    #[proptest]
+    fn proven_security_does_not_exceed_conjectured_security(
+        #[strategy(1_u32..8)] log2_expansion_factor: u32,
+        #[strategy(0_usize..200)] num_queries: usize,
+        #[strategy(0_u32..30)] num_grinding_bits: u32,
+        #[strategy(32_u32..400)] log2_field_size: u32,
+        #[strategy(0_u32..30)] log2_trace_length: u32,
+        #[strategy(proptest::collection::vec(1_usize..20, 0..50))] constraint_degrees: Vec<usize>,
+    ) {
+        let parameters = Parameters {
+            fri_expansion_factor: 1 << log2_expansion_factor,
+            num_queries,
+            num_grinding_bits,
+            log2_field_size,
+            trace_length: 1 << log2_trace_length,
+            constraint_degrees,
+        };
+        let security_level = estimate(&parameters).unwrap();
+        prop_assert!(security_level.proven <= security_level.conjectured);
+    }
+
+    #[proptest]
+    fn more_queries_do_not_decrease_security(#[strategy(0_usize..200)] num_queries: usize) {
+        let parameters = Parameters {
+            num_queries,
+            ..parameters()
+        };
+        let more_queries = Parameters {
+            num_queries: num_queries + 1,
+            ..parameters.clone()
+        };
+        let security_level = estimate(&parameters).unwrap();
+        let more_security = estimate(&more_queries).unwrap();
+        prop_assert!(security_level.conjectured <= more_security.conjectured);
+        prop_assert!(security_level.proven <= more_security.proven);
+    }
+
+    #[test]
+    fn invalid_parameters_are_rejected() {
+        for fri_expansion_factor in [0, 1, 3, 6] {
+            let parameters = Parameters {
+                fri_expansion_factor,
+                ..parameters()
+            };
+            let err = estimate(&parameters).unwrap_err();
+            let expected = SecurityLevelError::InvalidExpansionFactor(fri_expansion_factor);
+            assert_eq!(expected, err);
+        }
+
+        for trace_length in [0, 3, 1000] {
+            let parameters = Parameters {
+                trace_length,
+                ..parameters()
+            };
+            let err = estimate(&parameters).unwrap_err();
+            assert_eq!(SecurityLevelError::InvalidTraceLength(trace_length), err);
+        }
+
+        let parameters = Parameters {
+            trace_length: 1 << (usize::BITS - 1),
+            ..parameters()
+        };
+        let err = estimate(&parameters).unwrap_err();
+        assert_eq!(SecurityLevelError::DomainTooLarge, err);
+    }
+}
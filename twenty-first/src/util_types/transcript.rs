@@ -0,0 +1,362 @@
+use std::sync::atomic::AtomicBool;
+
+use itertools::Itertools;
+
+use crate::math::b_field_element::BFieldElement;
+use crate::math::bfield_codec::BFieldCodec;
+use crate::math::digest::Digest;
+use crate::math::x_field_element::XFieldElement;
+use crate::util_types::algebraic_hasher::AlgebraicHasher;
+use crate::util_types::pow;
+
+/// A [Fiat–Shamir][fiat_shamir] transcript built on top of a [`Sponge`][sponge].
+///
+/// Every item that is absorbed and every challenge that is sampled carries a label. The label is
+/// absorbed alongside the item (respectively before the challenge is squeezed), binding each
+/// challenge to the exact sequence of labeled messages that preceded it. Compared to hashing
+/// (prefixes of) a serialized proof, this makes it impossible to accidentally derive the same
+/// challenge for two different purposes or to forget part of the prover's messages.
+///
+/// Every transcript is additionally bound to a protocol label upon [creation](Self::new), which
+/// separates challenges of different protocols even if the sequence of messages coincides.
+///
+/// Prover and verifier must perform the exact same sequence of calls in order to arrive at the
+/// same challenges.
+///
+/// Before sampling challenges, the prover can be required to [grind](Self::grind), that is, to
+/// solve a [proof-of-work](pow) puzzle derived from the current state of the transcript. Every
+/// grinding bit multiplies the cost of re-trying the protocol with a different transcript by 2,
+/// which buys security at the expense of prover time instead of, _e.g._, additional queries.
+///
+/// [fiat_shamir]: https://en.wikipedia.org/wiki/Fiat%E2%80%93Shamir_heuristic
+/// [sponge]: crate::util_types::algebraic_hasher::Sponge
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Transcript<H>
+where
+    H: AlgebraicHasher,
+{
+    sponge: H,
+}
+
+/// Distinguishes the different kinds of operations on a [`Transcript`] inside the sponge.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Operation {
+    Protocol = 0,
+    Absorb = 1,
+    Sample = 2,
+}
+
+impl<H> Transcript<H>
+where
+    H: AlgebraicHasher,
+{
+    /// Start a new transcript for the protocol identified by the given label, for example
+    /// `"twenty-first-pow-v1"`.
+    pub fn new(protocol: &'static str) -> Self {
+        let mut transcript = Self { sponge: H::init() };
+        let message = Self::encode_label(Operation::Protocol, protocol);
+        transcript.sponge.pad_and_absorb_all(&message);
+        transcript
+    }
+
+    /// Absorb a sequence of [`BFieldElement`]s. The length of the sequence is absorbed as well,
+    /// so that sequences of different lengths can never be confused with one another.
+    pub fn absorb_elements(&mut self, label: &'static str, elements: &[BFieldElement]) {
+        let length = BFieldElement::new(elements.len() as u64);
+        let label = Self::encode_label(Operation::Absorb, label);
+        let message = [label, vec![length], elements.to_vec()].concat();
+        self.sponge.pad_and_absorb_all(&message);
+    }
+
+    /// Absorb a single [`Digest`], for example a Merkle root.
+    pub fn absorb_digest(&mut self, label: &'static str, digest: Digest) {
+        self.absorb_elements(label, &digest.values());
+    }
+
+    /// Absorb any item that can be [encoded](BFieldCodec) as a sequence of [`BFieldElement`]s.
+    pub fn absorb<T: BFieldCodec>(&mut self, label: &'static str, item: &T) {
+        self.absorb_elements(label, &item.encode());
+    }
+
+    /// Sample `num_scalars` [`XFieldElement`]s. See [`AlgebraicHasher::sample_scalars`].
+    pub fn sample_scalars(
+        &mut self,
+        label: &'static str,
+        num_scalars: usize,
+    ) -> Vec<XFieldElement> {
+        self.absorb_label(label);
+        self.sponge.sample_scalars(num_scalars)
+    }
+
+    /// Sample a single [`XFieldElement`].
+    pub fn sample_scalar(&mut self, label: &'static str) -> XFieldElement {
+        self.sample_scalars(label, 1)[0]
+    }
+
+    /// Sample `num_indices` indices in the range `[0, upper_bound)`. The `upper_bound` must be a
+    /// power of 2. See [`AlgebraicHasher::sample_indices`].
+    pub fn sample_indices(
+        &mut self,
+        label: &'static str,
+        upper_bound: u32,
+        num_indices: usize,
+    ) -> Vec<u32> {
+        self.absorb_label(label);
+        self.sponge.sample_indices(upper_bound, num_indices)
+    }
+
+    /// Sample a [`Digest`], for example to seed further randomness derivation.
+    pub fn sample_digest(&mut self, label: &'static str) -> Digest {
+        self.absorb_label(label);
+        let squeezed = self.sponge.squeeze();
+        Digest::new(squeezed[..Digest::LEN].try_into().unwrap())
+    }
+
+    /// Grind for `num_bits` bits: find a nonce solving the [proof-of-work](pow) puzzle derived
+    /// from the current state of the transcript, absorb it, and return it. The nonce must be
+    /// sent to the verifier, who calls [`verify_grind`](Self::verify_grind).
+    ///
+    /// Grinding for 0 bits leaves the transcript untouched and returns nonce 0.
+    ///
+    /// The search can be aborted by setting `cancel` to `true`, in which case `None` is
+    /// returned.
+    ///
+    /// # Panics
+    ///
+    /// - Panics if `num_bits` is not a valid number of leading zeros for [`pow::target`].
+    pub fn grind(
+        &mut self,
+        label: &'static str,
+        num_bits: u32,
+        cancel: &AtomicBool,
+    ) -> Option<u64> {
+        if num_bits == 0 {
+            return Some(0);
+        }
+
+        let prefix = self.sample_digest(label);
+        let nonce = pow::solve(prefix, pow::target(num_bits), cancel)?;
+        self.absorb(label, &nonce);
+        Some(nonce)
+    }
+
+    /// Check the nonce produced by [grinding](Self::grind) for `num_bits` bits, absorbing it.
+    /// After successful verification, the transcript is in the same state as the prover's.
+    ///
+    /// For 0 bits, only nonce 0 is accepted, and the transcript is left untouched.
+    ///
+    /// # Panics
+    ///
+    /// - Panics if `num_bits` is not a valid number of leading zeros for [`pow::target`].
+    pub fn verify_grind(&mut self, label: &'static str, num_bits: u32, nonce: u64) -> bool {
+        if num_bits == 0 {
+            return nonce == 0;
+        }
+
+        let prefix = self.sample_digest(label);
+        if !pow::verify(prefix, pow::target(num_bits), nonce) {
+            return false;
+        }
+        self.absorb(label, &nonce);
+        true
+    }
+
+    fn absorb_label(&mut self, label: &'static str) {
+        let message = Self::encode_label(Operation::Sample, label);
+        self.sponge.pad_and_absorb_all(&message);
+    }
+
+    /// An injective encoding of the operation and its label: the operation, the label's length
+    /// in bytes, and the bytes themselves.
+    fn encode_label(operation: Operation, label: &'static str) -> Vec<BFieldElement> {
+        let operation = BFieldElement::new(operation as u64);
+        let length = BFieldElement::new(label.len() as u64);
+        let bytes = label.bytes().map(|byte| BFieldElement::new(byte.into()));
+        [operation, length].into_iter().chain(bytes).collect_vec()
+    }
+}
+
+#[cfg(test)]
+mod transcript_tests {
+    use proptest::prelude::*;
+    use proptest_arbitrary_interop::arb;
+    use test_strategy::proptest;
+
+    use crate::math::tip5::Tip5;
+
+    use super::*;
+
+    #[proptest]
+    fn identical_transcripts_produce_identical_challenges(
+        #[strategy(arb())] root: Digest,
+        #[strategy(arb())] elements: Vec<BFieldElement>,
+    ) {
+        let mut prover = Transcript::<Tip5>::new("test");
+        let mut verifier = Transcript::<Tip5>::new("test");
+        for transcript in [&mut prover, &mut verifier] {
+            transcript.absorb_digest("root", root);
+            transcript.absorb_elements("elements", &elements);
+        }
+
+        prop_assert_eq!(
+            prover.sample_scalar("alpha"),
+            verifier.sample_scalar("alpha")
+        );
+        let prover_indices = prover.sample_indices("indices", 1 << 10, 20);
+        let verifier_indices = verifier.sample_indices("indices", 1 << 10, 20);
+        prop_assert_eq!(prover_indices, verifier_indices);
+    }
+
+    #[proptest]
+    fn absorbing_different_items_changes_challenges(
+        #[strategy(arb())] item: Digest,
+        #[strategy(arb())]
+        #[filter(#item != #other_item)]
+        other_item: Digest,
+    ) {
+        let mut transcript = Transcript::<Tip5>::new("test");
+        let mut other_transcript = transcript.clone();
+        transcript.absorb_digest("root", item);
+        other_transcript.absorb_digest("root", other_item);
+
+        let challenge = transcript.sample_scalar("alpha");
+        let other_challenge = other_transcript.sample_scalar("alpha");
+        prop_assert_ne!(challenge, other_challenge);
+    }
+
+    #[test]
+    fn labels_separate_challenges() {
+        let mut transcript = Transcript::<Tip5>::new("test");
+        let mut other_transcript = transcript.clone();
+        assert_ne!(
+            transcript.sample_scalar("alpha"),
+            other_transcript.sample_scalar("beta")
+        );
+    }
+
+    #[test]
+    fn labels_separate_absorbed_items() {
+        let mut transcript = Transcript::<Tip5>::new("test");
+        let mut other_transcript = transcript.clone();
+        transcript.absorb_digest("base-table-root", Digest::default());
+        other_transcript.absorb_digest("extension-table-root", Digest::default());
+        assert_ne!(transcript, other_transcript);
+    }
+
+    #[test]
+    fn absorbed_sequences_of_different_length_are_distinguished() {
+        let mut transcript = Transcript::<Tip5>::new("test");
+        let mut other_transcript = transcript.clone();
+        transcript.absorb_elements("elements", &[]);
+        other_transcript.absorb_elements("elements", &[BFieldElement::new(0)]);
+        assert_ne!(transcript, other_transcript);
+    }
+
+    #[test]
+    fn labels_separate_sampled_digests() {
+        let mut transcript = Transcript::<Tip5>::new("test");
+        let mut other_transcript = transcript.clone();
+        assert_ne!(
+            transcript.sample_digest("seed"),
+            other_transcript.sample_digest("other seed")
+        );
+    }
+
+    #[test]
+    fn sampled_indices_are_in_range() {
+        let mut transcript = Transcript::<Tip5>::new("test");
+        let indices = transcript.sample_indices("indices", 64, 100);
+        assert_eq!(100, indices.len());
+        assert!(indices.into_iter().all(|index| index < 64));
+    }
+
+    #[test]
+    fn protocol_labels_separate_challenges() {
+        let mut transcript = Transcript::<Tip5>::new("some protocol");
+        let mut other_transcript = Transcript::<Tip5>::new("other protocol");
+        assert_ne!(
+            transcript.sample_scalar("alpha"),
+            other_transcript.sample_scalar("alpha")
+        );
+    }
+
+    #[test]
+    fn absorbing_without_elements_differs_from_sampling() {
+        let mut transcript = Transcript::<Tip5>::new("test");
+        let mut other_transcript = transcript.clone();
+        transcript.absorb_elements("alpha", &[]);
+        other_transcript.absorb_label("alpha");
+        assert_ne!(transcript, other_transcript);
+    }
+
+    #[proptest(cases = 10)]
+    fn ground_transcripts_produce_identical_challenges(#[strategy(arb())] root: Digest) {
+        let mut prover = Transcript::<Tip5>::new("test");
+        prover.absorb_digest("root", root);
+        let mut verifier = prover.clone();
+
+        let nonce = prover
+            .grind("grinding", 6, &AtomicBool::new(false))
+            .unwrap();
+        prop_assert!(verifier.verify_grind("grinding", 6, nonce));
+        prop_assert_eq!(
+            prover.sample_scalar("alpha"),
+            verifier.sample_scalar("alpha")
+        );
+    }
+
+    #[proptest(cases = 10)]
+    fn grinding_binds_challenges_to_nonce(#[strategy(arb())] root: Digest) {
+        let mut prover = Transcript::<Tip5>::new("test");
+        prover.absorb_digest("root", root);
+        let mut verifier = prover.clone();
+        let prefix = prover.clone().sample_digest("grinding");
+
+        let nonce = prover
+            .grind("grinding", 6, &AtomicBool::new(false))
+            .unwrap();
+        let other_nonce = (nonce + 1..)
+            .find(|&n| pow::verify(prefix, pow::target(6), n))
+            .unwrap();
+        prop_assert!(verifier.verify_grind("grinding", 6, other_nonce));
+        prop_assert_ne!(
+            prover.sample_scalar("alpha"),
+            verifier.sample_scalar("alpha")
+        );
+    }
+
+    #[proptest(cases = 10)]
+    fn invalid_nonce_does_not_verify(#[strategy(arb())] root: Digest) {
+        let mut transcript = Transcript::<Tip5>::new("test");
+        transcript.absorb_digest("root", root);
+        let prefix = transcript.clone().sample_digest("grinding");
+        let invalid_nonce = (0..)
+            .find(|&n| !pow::verify(prefix, pow::target(6), n))
+            .unwrap();
+        prop_assert!(!transcript.verify_grind("grinding", 6, invalid_nonce));
+    }
+
+    #[test]
+    fn grinding_for_zero_bits_is_a_no_op() {
+        let mut prover = Transcript::<Tip5>::new("test");
+        let mut verifier = prover.clone();
+        let unchanged_transcript = prover.clone();
+
+        let nonce = prover
+            .grind("grinding", 0, &AtomicBool::new(false))
+            .unwrap();
+        assert_eq!(0, nonce);
+        assert_eq!(unchanged_transcript, prover);
+
+        assert!(!verifier.verify_grind("grinding", 0, 1));
+        assert!(verifier.verify_grind("grinding", 0, nonce));
+        assert_eq!(unchanged_transcript, verifier);
+    }
+
+    #[test]
+    fn cancelled_grinding_returns_nothing() {
+        let mut transcript = Transcript::<Tip5>::new("test");
+        let cancel = AtomicBool::new(true);
+        assert_eq!(None, transcript.grind("grinding", 200, &cancel));
+    }
+}
@@ -0,0 +1,405 @@
+//! [Winternitz one-time signatures][wots] (WOTS+) over [`Tip5`].
+//!
+//! A secret key consists of [`NUM_CHAINS`] secret chain starts, all derived from one secret seed.
+//! The public key is the hash of the ends of the corresponding hash chains, each of length
+//! [`WINTERNITZ_PARAMETER`]` - 1`. To sign a [`Digest`], it is split into base-`w` digits
+//! (followed by a checksum), and every chain is walked as many steps as its digit indicates.
+//! The verifier walks the remaining steps and compares the resulting public key.
+//!
+//! Every step of every chain is a tweakable hash: the public seed, the key's index, the chain's
+//! index, and the position in the chain are hashed alongside the chain value. This makes
+//! multi-target attacks across chains and keys infeasible, which is why the scheme can be used
+//! as a building block for many-time signature schemes where many keys share one public seed.
+//!
+//! A secret key must be used to sign **at most one** message. Signing two different messages
+//! with the same key reveals enough intermediate chain values to forge signatures.
+//!
+//! [wots]: https://eprint.iacr.org/2017/965
+
+use itertools::Itertools;
+use num_traits::ConstZero;
+use rand::Rng;
+use rand_distr::Distribution;
+use rand_distr::Standard;
+use serde::Deserialize;
+use serde::Serialize;
+use serde_big_array::BigArray;
+
+use crate::math::b_field_element::BFieldElement;
+use crate::math::bfield_codec::BFieldCodec;
+use crate::math::digest::Digest;
+use crate::math::tip5::Tip5;
+use crate::util_types::algebraic_hasher::AlgebraicHasher;
+
+/// The base-2 logarithm of the [`WINTERNITZ_PARAMETER`].
+pub const LOG2_WINTERNITZ_PARAMETER: usize = 4;
+
+/// The Winternitz parameter `w`. Each hash chain has length `w - 1`, and each chain signs one
+/// base-`w` digit.
+pub const WINTERNITZ_PARAMETER: usize = 1 << LOG2_WINTERNITZ_PARAMETER;
+
+/// The number of chains signing the message digits.
+pub const NUM_MESSAGE_CHAINS: usize = Digest::LEN * 64 / LOG2_WINTERNITZ_PARAMETER;
+
+/// The number of chains signing the checksum digits.
+///
+/// The checksum is at most `NUM_MESSAGE_CHAINS · (w - 1)`, which requires 3 base-`w` digits.
+pub const NUM_CHECKSUM_CHAINS: usize = 3;
+
+/// The total number of hash chains per key.
+pub const NUM_CHAINS: usize = NUM_MESSAGE_CHAINS + NUM_CHECKSUM_CHAINS;
+
+/// The length of each hash chain.
+const CHAIN_LENGTH: usize = WINTERNITZ_PARAMETER - 1;
+
+// Domain separators for the different uses of the hash function.
+const SECRET_CHAIN_START_DOMAIN: u64 = 0;
+const CHAIN_STEP_DOMAIN: u64 = 1;
+const PUBLIC_KEY_DOMAIN: u64 = 2;
+
+/// A WOTS+ secret key. Use it to sign at most one message.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SecretKey {
+    secret_seed: Digest,
+    public_seed: Digest,
+    key_index: u64,
+}
+
+/// A WOTS+ public key.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, BFieldCodec)]
+pub struct PublicKey {
+    pub public_seed: Digest,
+    pub key_index: u64,
+
+    /// The hash of the ends of all hash chains.
+    pub digest: Digest,
+}
+
+/// A WOTS+ signature: one intermediate value per hash chain.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize, BFieldCodec)]
+pub struct Signature {
+    #[serde(with = "BigArray")]
+    chain_values: [Digest; NUM_CHAINS],
+}
+
+impl SecretKey {
+    /// Deterministically derive a secret key from the given seeds.
+    ///
+    /// The `secret_seed` must be kept secret. The `public_seed` and the `key_index` become part
+    /// of the public key; they allow deriving many independent keys from the same seeds.
+    pub fn new(secret_seed: Digest, public_seed: Digest, key_index: u64) -> Self {
+        Self {
+            secret_seed,
+            public_seed,
+            key_index,
+        }
+    }
+
+    pub fn public_key(&self) -> PublicKey {
+        let chain_ends = (0..NUM_CHAINS)
+            .map(|chain_index| {
+                let chain_start = self.chain_start(chain_index);
+                self.chain(chain_index, chain_start, 0, CHAIN_LENGTH)
+            })
+            .collect_vec();
+
+        PublicKey {
+            public_seed: self.public_seed,
+            key_index: self.key_index,
+            digest: compress_chain_ends(&chain_ends),
+        }
+    }
+
+    /// Sign the given message.
+    ///
+    /// Every chain is walked in its entirety, independent of the message; the signature's chain
+    /// values are selected without branching on the message's digits. Signing therefore takes
+    /// the same number of hash invocations for every message.
+    pub fn sign(&self, message: Digest) -> Signature {
+        let digits = digits(message);
+        let chain_values = (0..NUM_CHAINS)
+            .map(|chain_index| {
+                let digit = digits[chain_index];
+                let mut chain_value = self.chain_start(chain_index);
+                let mut selected_value = Digest::ALL_ZERO;
+                for position in 0..=CHAIN_LENGTH {
+                    selected_value = select_if_equal(position, digit, chain_value, selected_value);
+                    if position < CHAIN_LENGTH {
+                        chain_value = self.step(chain_index, position, chain_value);
+                    }
+                }
+                selected_value
+            })
+            .collect_vec();
+
+        Signature {
+            chain_values: chain_values.try_into().unwrap(),
+        }
+    }
+
+    fn chain_start(&self, chain_index: usize) -> Digest {
+        let input = [
+            vec![BFieldElement::new(SECRET_CHAIN_START_DOMAIN)],
+            self.secret_seed.encode(),
+            self.key_index.encode(),
+            vec![BFieldElement::new(chain_index as u64)],
+        ]
+        .concat();
+        Tip5::hash_varlen(&input)
+    }
+
+    fn chain(&self, chain_index: usize, value: Digest, start: usize, num_steps: usize) -> Digest {
+        chain(
+            self.public_seed,
+            self.key_index,
+            chain_index,
+            value,
+            start,
+            num_steps,
+        )
+    }
+
+    fn step(&self, chain_index: usize, position: usize, value: Digest) -> Digest {
+        step(
+            self.public_seed,
+            self.key_index,
+            chain_index,
+            position,
+            value,
+        )
+    }
+}
+
+impl PublicKey {
+    pub fn verify(&self, message: Digest, signature: &Signature) -> bool {
+        let public_key = signature.recover_public_key(message, self.public_seed, self.key_index);
+        *self == public_key
+    }
+}
+
+impl Signature {
+    /// Compute the public key under which this signature is valid for the given message. In
+    /// particular, the signature is valid if and only if the returned public key matches the
+    /// expected one.
+    pub fn recover_public_key(
+        &self,
+        message: Digest,
+        public_seed: Digest,
+        key_index: u64,
+    ) -> PublicKey {
+        let digits = digits(message);
+        let chain_ends = (0..NUM_CHAINS)
+            .map(|chain_index| {
+                let digit = digits[chain_index];
+                let value = self.chain_values[chain_index];
+                let num_steps = CHAIN_LENGTH - digit;
+                chain(public_seed, key_index, chain_index, value, digit, num_steps)
+            })
+            .collect_vec();
+
+        PublicKey {
+            public_seed,
+            key_index,
+            digest: compress_chain_ends(&chain_ends),
+        }
+    }
+}
+
+impl Distribution<SecretKey> for Standard {
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> SecretKey {
+        SecretKey::new(rng.gen(), rng.gen(), 0)
+    }
+}
+
+/// The message's base-`w` digits, followed by the base-`w` digits of the checksum.
+fn digits(message: Digest) -> [usize; NUM_CHAINS] {
+    let digit_mask = (WINTERNITZ_PARAMETER - 1) as u64;
+    let digits_per_element = 64 / LOG2_WINTERNITZ_PARAMETER;
+    let message_digits = message.values().into_iter().flat_map(|element| {
+        let value = element.value();
+        (0..digits_per_element)
+            .map(move |i| ((value >> (i * LOG2_WINTERNITZ_PARAMETER)) & digit_mask) as usize)
+    });
+
+    let mut digits = [0; NUM_CHAINS];
+    for (digit, message_digit) in digits.iter_mut().zip(message_digits) {
+        *digit = message_digit;
+    }
+
+    let checksum: usize = digits[..NUM_MESSAGE_CHAINS]
+        .iter()
+        .map(|&digit| CHAIN_LENGTH - digit)
+        .sum();
+    for (i, digit) in digits[NUM_MESSAGE_CHAINS..].iter_mut().enumerate() {
+        *digit = (checksum >> (i * LOG2_WINTERNITZ_PARAMETER)) & (WINTERNITZ_PARAMETER - 1);
+    }
+
+    digits
+}
+
+/// Walk `num_steps` steps along the indicated chain, starting from `value` at position `start`.
+fn chain(
+    public_seed: Digest,
+    key_index: u64,
+    chain_index: usize,
+    value: Digest,
+    start: usize,
+    num_steps: usize,
+) -> Digest {
+    (start..start + num_steps).fold(value, |value, position| {
+        step(public_seed, key_index, chain_index, position, value)
+    })
+}
+
+fn step(
+    public_seed: Digest,
+    key_index: u64,
+    chain_index: usize,
+    position: usize,
+    value: Digest,
+) -> Digest {
+    let input = [
+        vec![BFieldElement::new(CHAIN_STEP_DOMAIN)],
+        public_seed.encode(),
+        key_index.encode(),
+        vec![
+            BFieldElement::new(chain_index as u64),
+            BFieldElement::new(position as u64),
+        ],
+        value.encode(),
+    ]
+    .concat();
+    Tip5::hash_varlen(&input)
+}
+
+fn compress_chain_ends(chain_ends: &[Digest]) -> Digest {
+    let chain_ends = chain_ends.iter().flat_map(|digest| digest.values());
+    let input = [BFieldElement::new(PUBLIC_KEY_DOMAIN)]
+        .into_iter()
+        .chain(chain_ends)
+        .collect_vec();
+    Tip5::hash_varlen(&input)
+}
+
+/// Returns `candidate` if `position == digit`, and `current` otherwise, without branching.
+fn select_if_equal(position: usize, digit: usize, candidate: Digest, current: Digest) -> Digest {
+    let difference = (position ^ digit) as u64;
+    let is_equal = ((difference | difference.wrapping_neg()) >> 63) ^ 1;
+    let mask = is_equal.wrapping_neg();
+
+    let mut selected = [BFieldElement::ZERO; Digest::LEN];
+    for (i, element) in selected.iter_mut().enumerate() {
+        let candidate = candidate.0[i].raw_u64();
+        let current = current.0[i].raw_u64();
+        *element = BFieldElement::from_raw_u64((candidate & mask) | (current & !mask));
+    }
+    Digest::new(selected)
+}
+
+#[cfg(test)]
+mod wots_tests {
+    use proptest::prelude::*;
+    use proptest_arbitrary_interop::arb;
+    use test_strategy::proptest;
+
+    use super::*;
+
+    #[test]
+    fn number_of_checksum_chains_suffices_for_largest_checksum() {
+        let max_checksum = NUM_MESSAGE_CHAINS * CHAIN_LENGTH;
+        let max_representable_checksum =
+            (1 << (NUM_CHECKSUM_CHAINS * LOG2_WINTERNITZ_PARAMETER)) - 1;
+        assert!(max_checksum <= max_representable_checksum);
+    }
+
+    #[test]
+    fn all_digits_are_in_range() {
+        let digits = digits(Digest::new(
+            [BFieldElement::new(BFieldElement::MAX); Digest::LEN],
+        ));
+        assert!(digits.into_iter().all(|digit| digit < WINTERNITZ_PARAMETER));
+    }
+
+    #[test]
+    fn branchless_selection_selects_correctly() {
+        let candidate = Digest::new([BFieldElement::new(42); Digest::LEN]);
+        let current = Digest::new([BFieldElement::new(1337); Digest::LEN]);
+        for position in 0..WINTERNITZ_PARAMETER {
+            for digit in 0..WINTERNITZ_PARAMETER {
+                let expected = if position == digit {
+                    candidate
+                } else {
+                    current
+                };
+                let selected = select_if_equal(position, digit, candidate, current);
+                assert_eq!(expected, selected);
+            }
+        }
+    }
+
+    #[proptest(cases = 10)]
+    fn signature_verifies(#[strategy(arb())] message: Digest) {
+        let secret_key: SecretKey = rand::random();
+        let public_key = secret_key.public_key();
+        let signature = secret_key.sign(message);
+        prop_assert!(public_key.verify(message, &signature));
+    }
+
+    #[proptest(cases = 10)]
+    fn signature_does_not_verify_for_different_message(
+        #[strategy(arb())] message: Digest,
+        #[strategy(arb())]
+        #[filter(#message != #other_message)]
+        other_message: Digest,
+    ) {
+        let secret_key: SecretKey = rand::random();
+        let public_key = secret_key.public_key();
+        let signature = secret_key.sign(message);
+        prop_assert!(!public_key.verify(other_message, &signature));
+    }
+
+    #[proptest(cases = 10)]
+    fn signature_does_not_verify_under_different_key_index(
+        #[strategy(arb())] message: Digest,
+        #[strategy(arb())] secret_seed: Digest,
+        #[strategy(arb())] public_seed: Digest,
+    ) {
+        let secret_key = SecretKey::new(secret_seed, public_seed, 0);
+        let other_secret_key = SecretKey::new(secret_seed, public_seed, 1);
+        let signature = secret_key.sign(message);
+        prop_assert!(!other_secret_key.public_key().verify(message, &signature));
+    }
+
+    #[test]
+    fn manipulated_signature_does_not_verify() {
+        let secret_key: SecretKey = rand::random();
+        let public_key = secret_key.public_key();
+        let message = rand::random();
+        let mut signature = secret_key.sign(message);
+        signature.chain_values[NUM_CHAINS - 1] = rand::random();
+        assert!(!public_key.verify(message, &signature));
+    }
+
+    #[test]
+    fn serialization_deserialization_test() {
+        let secret_key: SecretKey = rand::random();
+        let public_key = secret_key.public_key();
+        let signature = secret_key.sign(rand::random());
+
+        let secret_key_as_json = serde_json::to_string(&secret_key).unwrap();
+        let secret_key_again: SecretKey = serde_json::from_str(&secret_key_as_json).unwrap();
+        assert_eq!(secret_key, secret_key_again);
+
+        let public_key_as_json = serde_json::to_string(&public_key).unwrap();
+        let public_key_again: PublicKey = serde_json::from_str(&public_key_as_json).unwrap();
+        assert_eq!(public_key, public_key_again);
+
+        let signature_as_json = serde_json::to_string(&signature).unwrap();
+        let signature_again: Signature = serde_json::from_str(&signature_as_json).unwrap();
+        assert_eq!(signature, signature_again);
+
+        let signature_as_bytes = bincode::serialize(&signature).unwrap();
+        let signature_from_bytes: Signature = bincode::deserialize(&signature_as_bytes).unwrap();
+        assert_eq!(signature, signature_from_bytes);
+    }
+}
@@ -0,0 +1,565 @@
+//! A stateful, many-time, hash-based signature scheme in the style of [XMSS][xmss].
+//!
+//! The key pair consists of `2^height` [Winternitz one-time key pairs](crate::util_types::wots),
+//! all derived from the same seeds. The public key is the root of the [Merkle tree](MerkleTree)
+//! whose leafs are the one-time public keys. A signature consists of a one-time signature and the
+//! authentication path of the used one-time key.
+//!
+//! The secret key is _stateful_: every one-time key must be used at most once, which is why
+//! [signing](SecretKey::sign) advances the secret key to the next one-time key. The updated
+//! secret key must be persisted before the signature is published.
+//!
+//! The authentication paths are produced by [Szydlo's variant][szydlo] of the BDS tree
+//! traversal. Instead of storing the entire Merkle tree, the secret key keeps the current
+//! authentication path and one partial tree-hash computation per level. Advancing the key
+//! computes at most `2·height - 1` one-time public keys.
+//!
+//! [xmss]: https://eprint.iacr.org/2011/484
+//! [szydlo]: https://www.szydlo.com/szydlo-loglog.pdf
+
+use std::marker::PhantomData;
+
+use itertools::Itertools;
+use rayon::prelude::*;
+use serde::Deserialize;
+use serde::Serialize;
+use thiserror::Error;
+
+use crate::math::bfield_codec::BFieldCodec;
+use crate::math::digest::Digest;
+use crate::math::tip5::Tip5;
+use crate::util_types::algebraic_hasher::AlgebraicHasher;
+use crate::util_types::merkle_tree::CpuParallel;
+use crate::util_types::merkle_tree::MerkleTree;
+use crate::util_types::merkle_tree::MerkleTreeError;
+use crate::util_types::merkle_tree::MerkleTreeInclusionProof;
+use crate::util_types::merkle_tree::MAX_TREE_HEIGHT;
+use crate::util_types::wots;
+
+type Result<T> = std::result::Result<T, XmssError>;
+
+/// A stateful XMSS secret key. Every call to [`sign`](Self::sign) consumes one of the
+/// `2^height` one-time keys.
+///
+/// Deserialization rejects secret keys with inconsistent state.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "UncheckedSecretKey")]
+pub struct SecretKey {
+    secret_seed: Digest,
+    public_seed: Digest,
+    root: Digest,
+    height: usize,
+
+    /// The index of the one-time key used for the next signature.
+    next_key_index: u64,
+
+    /// The authentication path of the one-time key with index `next_key_index`, bottom-up.
+    authentication_path: Vec<Digest>,
+
+    /// The tree-hash instances computing the next authentication path node of every level.
+    /// An instance is absent if its level needs no further authentication path nodes.
+    tree_hashes: Vec<Option<TreeHash>>,
+}
+
+/// A [`SecretKey`] as deserialized, before its state is validated.
+#[derive(Deserialize)]
+struct UncheckedSecretKey {
+    secret_seed: Digest,
+    public_seed: Digest,
+    root: Digest,
+    height: usize,
+    next_key_index: u64,
+    authentication_path: Vec<Digest>,
+    tree_hashes: Vec<Option<TreeHash>>,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct PublicKey {
+    pub root: Digest,
+    pub public_seed: Digest,
+    pub height: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, BFieldCodec)]
+pub struct Signature {
+    pub key_index: u64,
+    pub wots_signature: wots::Signature,
+
+    /// The authentication path of the one-time public key, bottom-up.
+    pub authentication_path: Vec<Digest>,
+}
+
+/// The incremental computation of one node of the Merkle tree, one leaf at a time.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct TreeHash {
+    /// The height of the node to compute. Leafs have height 0.
+    height: usize,
+    next_leaf_index: u64,
+
+    /// Intermediate nodes, together with their heights.
+    stack: Vec<(usize, Digest)>,
+    node: Option<Digest>,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Error)]
+#[non_exhaustive]
+pub enum XmssError {
+    #[error("All {num_keys} one-time keys have been used.")]
+    KeysExhausted { num_keys: u64 },
+
+    #[error("Tree height must not exceed {MAX_TREE_HEIGHT}.")]
+    TreeTooHigh,
+
+    #[error("The secret key's state is inconsistent.")]
+    InvalidSecretKey,
+
+    #[error("Merkle tree error: {0}")]
+    MerkleTree(#[from] MerkleTreeError),
+}
+
+impl SecretKey {
+    /// Generate a key pair with `2^height` one-time keys.
+    ///
+    /// Key generation computes all one-time public keys and is therefore the most expensive
+    /// operation of the scheme.
+    pub fn new(secret_seed: Digest, public_seed: Digest, height: usize) -> Result<Self> {
+        if height > MAX_TREE_HEIGHT {
+            return Err(XmssError::TreeTooHigh);
+        }
+
+        let num_leafs = 1_u64 << height;
+        let leafs = (0..num_leafs)
+            .into_par_iter()
+            .map(|key_index| leaf(secret_seed, public_seed, key_index))
+            .collect::<Vec<_>>();
+        let tree = MerkleTree::<Tip5>::new::<CpuParallel>(&leafs)?;
+
+        // The node at the given height and the given position within that level.
+        let node = |node_height: usize, position: usize| {
+            let num_nodes_on_level = tree.num_leafs() >> node_height;
+            tree.node(num_nodes_on_level + position).unwrap()
+        };
+
+        // The first authentication path consists of the right siblings of the leftmost path.
+        // Every level's first update of the authentication path requires that level's leftmost
+        // node, which is therefore kept as a completed tree-hash instance.
+        let authentication_path = (0..height).map(|h| node(h, 1)).collect_vec();
+        let tree_hashes = (0..height)
+            .map(|h| Some(TreeHash::completed(h, node(h, 0))))
+            .collect_vec();
+
+        Ok(Self {
+            secret_seed,
+            public_seed,
+            root: tree.root(),
+            height,
+            next_key_index: 0,
+            authentication_path,
+            tree_hashes,
+        })
+    }
+
+    pub fn public_key(&self) -> PublicKey {
+        PublicKey {
+            root: self.root,
+            public_seed: self.public_seed,
+            height: self.height,
+        }
+    }
+
+    /// The total number of one-time keys.
+    pub fn num_keys(&self) -> u64 {
+        1 << self.height
+    }
+
+    /// The index of the one-time key that will be used for the next signature.
+    pub fn next_key_index(&self) -> u64 {
+        self.next_key_index
+    }
+
+    /// The number of signatures that can still be produced with this key.
+    pub fn num_remaining_signatures(&self) -> u64 {
+        self.num_keys() - self.next_key_index
+    }
+
+    /// Sign the given message, and advance the secret key to the next one-time key.
+    ///
+    /// The updated secret key must be persisted before the signature is published; signing with
+    /// an outdated copy of the secret key re-uses a one-time key and breaks security.
+    ///
+    /// # Errors
+    ///
+    /// - If all one-time keys have been used.
+    pub fn sign(&mut self, message: Digest) -> Result<Signature> {
+        if self.num_remaining_signatures() == 0 {
+            let num_keys = self.num_keys();
+            return Err(XmssError::KeysExhausted { num_keys });
+        }
+
+        let key_index = self.next_key_index;
+        let wots_secret_key = wots::SecretKey::new(self.secret_seed, self.public_seed, key_index);
+        let signature = Signature {
+            key_index,
+            wots_signature: wots_secret_key.sign(message),
+            authentication_path: self.authentication_path.clone(),
+        };
+
+        self.advance();
+        Ok(signature)
+    }
+
+    /// Update the authentication path to the next one-time key, and spend the budget of
+    /// `2·height - 1` leaf computations on the tree-hash instances.
+    fn advance(&mut self) {
+        let next_key_index = self.next_key_index + 1;
+        self.next_key_index = next_key_index;
+        if next_key_index == self.num_keys() {
+            return;
+        }
+
+        // The authentication path node of level `h` changes if and only if the next key index is
+        // a multiple of `2^h`.
+        let num_changing_levels = next_key_index.trailing_zeros() as usize + 1;
+        for h in 0..self.height.min(num_changing_levels) {
+            let tree_hash = self.tree_hashes[h].take().unwrap();
+            self.authentication_path[h] = self.complete(tree_hash);
+
+            let next_position = ((next_key_index >> h) + 1) ^ 1;
+            let first_leaf_index = next_position << h;
+            if first_leaf_index < self.num_keys() {
+                self.tree_hashes[h] = Some(TreeHash::new(h, first_leaf_index));
+            }
+        }
+
+        for _ in 0..(2 * self.height).saturating_sub(1) {
+            let Some(tree_hash) = self
+                .tree_hashes
+                .iter_mut()
+                .flatten()
+                .min_by_key(|t| t.low())
+            else {
+                break;
+            };
+            if tree_hash.node.is_some() {
+                break;
+            }
+            tree_hash.update(self.secret_seed, self.public_seed);
+        }
+    }
+
+    fn complete(&self, mut tree_hash: TreeHash) -> Digest {
+        debug_assert!(
+            tree_hash.node.is_some(),
+            "the tree-hash budget must suffice to complete every node in time"
+        );
+        while tree_hash.node.is_none() {
+            tree_hash.update(self.secret_seed, self.public_seed);
+        }
+        tree_hash.node.unwrap()
+    }
+}
+
+impl TryFrom<UncheckedSecretKey> for SecretKey {
+    type Error = XmssError;
+
+    fn try_from(unchecked: UncheckedSecretKey) -> Result<Self> {
+        let height = unchecked.height;
+        if height > MAX_TREE_HEIGHT {
+            return Err(XmssError::TreeTooHigh);
+        }
+
+        let secret_key = Self {
+            secret_seed: unchecked.secret_seed,
+            public_seed: unchecked.public_seed,
+            root: unchecked.root,
+            height,
+            next_key_index: unchecked.next_key_index,
+            authentication_path: unchecked.authentication_path,
+            tree_hashes: unchecked.tree_hashes,
+        };
+        if !secret_key.state_is_consistent() {
+            return Err(XmssError::InvalidSecretKey);
+        }
+        Ok(secret_key)
+    }
+}
+
+impl SecretKey {
+    /// Whether the state can be advanced without panicking. Requires a valid height.
+    fn state_is_consistent(&self) -> bool {
+        if self.next_key_index > self.num_keys()
+            || self.authentication_path.len() != self.height
+            || self.tree_hashes.len() != self.height
+        {
+            return false;
+        }
+
+        let tree_hashes_are_consistent = (0..).zip(&self.tree_hashes).all(|(h, tree_hash)| {
+            tree_hash.as_ref().is_none_or(|t| {
+                t.height == h && t.stack.iter().all(|&(node_height, _)| node_height < h)
+            })
+        });
+        if !tree_hashes_are_consistent {
+            return false;
+        }
+
+        // Advancing takes the tree-hash instance of every level whose authentication path node
+        // changes with the next signature.
+        let next_next_key_index = self.next_key_index + 1;
+        if next_next_key_index >= self.num_keys() {
+            return true;
+        }
+        let num_changing_levels = next_next_key_index.trailing_zeros() as usize + 1;
+        self.tree_hashes[..self.height.min(num_changing_levels)]
+            .iter()
+            .all(Option::is_some)
+    }
+}
+
+impl PublicKey {
+    pub fn verify(&self, message: Digest, signature: &Signature) -> bool {
+        if signature.authentication_path.len() != self.height {
+            return false;
+        }
+        let Ok(key_index) = usize::try_from(signature.key_index) else {
+            return false;
+        };
+
+        let wots_public_key = signature.wots_signature.recover_public_key(
+            message,
+            self.public_seed,
+            signature.key_index,
+        );
+        let inclusion_proof = MerkleTreeInclusionProof::<Tip5> {
+            tree_height: self.height,
+            indexed_leafs: vec![(key_index, wots_public_key.digest)],
+            authentication_structure: signature.authentication_path.clone(),
+            _hasher: PhantomData,
+        };
+        inclusion_proof.verify(self.root)
+    }
+}
+
+impl TreeHash {
+    fn new(height: usize, first_leaf_index: u64) -> Self {
+        Self {
+            height,
+            next_leaf_index: first_leaf_index,
+            stack: vec![],
+            node: None,
+        }
+    }
+
+    fn completed(height: usize, node: Digest) -> Self {
+        Self {
+            height,
+            next_leaf_index: 0,
+            stack: vec![],
+            node: Some(node),
+        }
+    }
+
+    /// The lowest height of any node on the stack. Completed instances are never lower than any
+    /// other instance.
+    fn low(&self) -> usize {
+        if self.node.is_some() {
+            return usize::MAX;
+        }
+        let lowest_height_on_stack = self.stack.iter().map(|&(height, _)| height).min();
+        lowest_height_on_stack.unwrap_or(self.height)
+    }
+
+    /// Compute the next leaf, and merge all nodes that can be merged.
+    fn update(&mut self, secret_seed: Digest, public_seed: Digest) {
+        let leaf = leaf(secret_seed, public_seed, self.next_leaf_index);
+        self.next_leaf_index += 1;
+
+        let mut node = (0, leaf);
+        while let Some(&(height, left_sibling)) = self.stack.last() {
+            if height != node.0 {
+                break;
+            }
+            self.stack.pop();
+            node = (height + 1, Tip5::hash_pair(left_sibling, node.1));
+        }
+
+        if node.0 == self.height {
+            self.node = Some(node.1);
+        } else {
+            self.stack.push(node);
+        }
+    }
+}
+
+fn leaf(secret_seed: Digest, public_seed: Digest, key_index: u64) -> Digest {
+    let wots_secret_key = wots::SecretKey::new(secret_seed, public_seed, key_index);
+    wots_secret_key.public_key().digest
+}
+
+#[cfg(test)]
+mod xmss_tests {
+    use proptest::prelude::*;
+    use proptest_arbitrary_interop::arb;
+    use test_strategy::proptest;
+
+    use super::*;
+
+    fn random_secret_key(height: usize) -> SecretKey {
+        SecretKey::new(rand::random(), rand::random(), height).unwrap()
+    }
+
+    #[test]
+    fn every_one_time_key_produces_valid_signatures() {
+        for height in 0..=4 {
+            let mut secret_key = random_secret_key(height);
+            let public_key = secret_key.public_key();
+            for key_index in 0..secret_key.num_keys() {
+                assert_eq!(key_index, secret_key.next_key_index());
+                let message = rand::random();
+                let signature = secret_key.sign(message).unwrap();
+                assert_eq!(key_index, signature.key_index);
+                assert!(public_key.verify(message, &signature));
+            }
+            assert_eq!(0, secret_key.num_remaining_signatures());
+        }
+    }
+
+    #[test]
+    fn authentication_paths_match_those_of_full_merkle_tree() {
+        let height = 5;
+        let mut secret_key = random_secret_key(height);
+        let leafs = (0..secret_key.num_keys())
+            .map(|i| leaf(secret_key.secret_seed, secret_key.public_seed, i))
+            .collect_vec();
+        let tree = MerkleTree::<Tip5>::new::<CpuParallel>(&leafs).unwrap();
+        assert_eq!(tree.root(), secret_key.public_key().root);
+
+        for key_index in 0..tree.num_leafs() {
+            let expected_path = tree.authentication_structure(&[key_index]).unwrap();
+            assert_eq!(expected_path, secret_key.authentication_path);
+            secret_key.sign(rand::random()).unwrap();
+        }
+    }
+
+    #[test]
+    fn signing_with_exhausted_key_fails() {
+        let mut secret_key = random_secret_key(1);
+        secret_key.sign(rand::random()).unwrap();
+        secret_key.sign(rand::random()).unwrap();
+
+        let err = secret_key.sign(rand::random()).unwrap_err();
+        assert_eq!(XmssError::KeysExhausted { num_keys: 2 }, err);
+    }
+
+    #[test]
+    fn too_high_tree_is_rejected() {
+        let err = SecretKey::new(rand::random(), rand::random(), MAX_TREE_HEIGHT + 1).unwrap_err();
+        assert_eq!(XmssError::TreeTooHigh, err);
+    }
+
+    #[proptest(cases = 10)]
+    fn signature_does_not_verify_for_different_message(
+        #[strategy(arb())] message: Digest,
+        #[strategy(arb())]
+        #[filter(#message != #other_message)]
+        other_message: Digest,
+    ) {
+        let mut secret_key = random_secret_key(2);
+        let public_key = secret_key.public_key();
+        let signature = secret_key.sign(message).unwrap();
+        prop_assert!(!public_key.verify(other_message, &signature));
+    }
+
+    #[test]
+    fn signature_with_manipulated_key_index_does_not_verify() {
+        let mut secret_key = random_secret_key(2);
+        let public_key = secret_key.public_key();
+        let message = rand::random();
+        let mut signature = secret_key.sign(message).unwrap();
+        signature.key_index = 1;
+        assert!(!public_key.verify(message, &signature));
+
+        signature.key_index = u64::MAX;
+        assert!(!public_key.verify(message, &signature));
+    }
+
+    #[test]
+    fn signature_with_authentication_path_of_wrong_length_does_not_verify() {
+        let mut secret_key = random_secret_key(2);
+        let public_key = secret_key.public_key();
+        let message = rand::random();
+        let mut signature = secret_key.sign(message).unwrap();
+        signature.authentication_path.pop();
+        assert!(!public_key.verify(message, &signature));
+    }
+
+    #[test]
+    fn serialized_secret_key_resumes_signing() {
+        let mut secret_key = random_secret_key(3);
+        let public_key = secret_key.public_key();
+        secret_key.sign(rand::random()).unwrap();
+
+        let secret_key_as_json = serde_json::to_string(&secret_key).unwrap();
+        let mut secret_key_again: SecretKey = serde_json::from_str(&secret_key_as_json).unwrap();
+        assert_eq!(secret_key, secret_key_again);
+
+        let message = rand::random();
+        let signature = secret_key_again.sign(message).unwrap();
+        assert_eq!(1, signature.key_index);
+        assert!(public_key.verify(message, &signature));
+
+        let signature_as_json = serde_json::to_string(&signature).unwrap();
+        let signature_again: Signature = serde_json::from_str(&signature_as_json).unwrap();
+        assert_eq!(signature, signature_again);
+
+        let public_key_as_json = serde_json::to_string(&public_key).unwrap();
+        let public_key_again: PublicKey = serde_json::from_str(&public_key_as_json).unwrap();
+        assert_eq!(public_key, public_key_again);
+    }
+
+    #[test]
+    fn secret_key_state_is_consistent_after_every_signature() {
+        let mut secret_key = random_secret_key(4);
+        for _ in 0..=secret_key.num_keys() {
+            let secret_key_as_json = serde_json::to_string(&secret_key).unwrap();
+            let secret_key_again: SecretKey = serde_json::from_str(&secret_key_as_json).unwrap();
+            assert_eq!(secret_key, secret_key_again);
+            let _ = secret_key.sign(rand::random());
+        }
+    }
+
+    #[test]
+    fn deserializing_inconsistent_secret_key_fails() {
+        let secret_key = random_secret_key(3);
+        type Manipulation = fn(&mut serde_json::Value);
+        let manipulations: [(XmssError, Manipulation); 7] = [
+            (XmssError::TreeTooHigh, |key| key["height"] = 64.into()),
+            (XmssError::InvalidSecretKey, |key| {
+                key["height"] = 4.into();
+            }),
+            (XmssError::InvalidSecretKey, |key| {
+                key["next_key_index"] = 9.into();
+            }),
+            (XmssError::InvalidSecretKey, |key| {
+                key["authentication_path"].as_array_mut().unwrap().pop();
+            }),
+            (XmssError::InvalidSecretKey, |key| {
+                key["tree_hashes"].as_array_mut().unwrap().pop();
+            }),
+            (XmssError::InvalidSecretKey, |key| {
+                key["tree_hashes"][0] = serde_json::Value::Null;
+            }),
+            (XmssError::InvalidSecretKey, |key| {
+                key["tree_hashes"][1]["height"] = 2.into();
+            }),
+        ];
+
+        let secret_key_as_json = serde_json::to_value(&secret_key).unwrap();
+        for (expected_err, manipulate) in manipulations {
+            let mut manipulated = secret_key_as_json.clone();
+            manipulate(&mut manipulated);
+            let err = serde_json::from_value::<SecretKey>(manipulated).unwrap_err();
+            assert_eq!(expected_err.to_string(), err.to_string());
+        }
+    }
+}